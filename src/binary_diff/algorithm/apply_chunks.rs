@@ -0,0 +1,162 @@
+use crate::binary_diff::binary_diff_chunk::BinaryDiffChunk;
+use crate::binary_diff::error::BinaryDiffError;
+use crate::binary_diff::helper::get_buffer_length;
+use crate::binary_diff::result::Result;
+use std::io::{BufReader, Read, Seek, SeekFrom, Write};
+
+// apply_chunks() should satisfy following requirements:
+//   - Each chunk starts at the end of the previous one (in offset of original binary)
+//   - Chunks cover whole original binary
+//   - Never panics on inconsistent chunks, but returns an error
+pub fn apply_chunks<R: Read + Seek, W: Write>(
+    chunks: &[BinaryDiffChunk],
+    old: &mut BufReader<R>,
+    out: &mut W,
+) -> Result<()> {
+    let old_size = get_buffer_length(old)?;
+    log::trace!("[*] old_size = {}", old_size);
+
+    let mut position = 0usize;
+    for chunk in chunks.iter() {
+        if *chunk.offset() != position {
+            return Err(BinaryDiffError::ChunkOffsetMismatchError(
+                position,
+                chunk.clone(),
+            ));
+        }
+        if old_size - position < chunk.original_length() {
+            return Err(BinaryDiffError::ChunkOutOfRangeError(
+                old_size,
+                chunk.clone(),
+            ));
+        }
+
+        match chunk {
+            BinaryDiffChunk::Same(_, length) => {
+                let copied = std::io::copy(&mut old.by_ref().take(*length as u64), out)
+                    .map_err(BinaryDiffError::IoError)?;
+                debug_assert_eq!(copied as usize, *length);
+            }
+            BinaryDiffChunk::Insert(_, bytes) => {
+                out.write_all(bytes).map_err(BinaryDiffError::IoError)?;
+            }
+            BinaryDiffChunk::Delete(_, length) => {
+                old.seek_relative(*length as i64)
+                    .map_err(BinaryDiffError::IoError)?;
+            }
+            BinaryDiffChunk::Replace(_, length, bytes) => {
+                old.seek_relative(*length as i64)
+                    .map_err(BinaryDiffError::IoError)?;
+                out.write_all(bytes).map_err(BinaryDiffError::IoError)?;
+            }
+            BinaryDiffChunk::Copy(_, source, length) => {
+                if *source > old_size || old_size - source < *length {
+                    return Err(BinaryDiffError::ChunkOutOfRangeError(
+                        old_size,
+                        chunk.clone(),
+                    ));
+                }
+                old.seek(SeekFrom::Start(*source as u64))
+                    .map_err(BinaryDiffError::IoError)?;
+                let copied = std::io::copy(&mut old.by_ref().take(*length as u64), out)
+                    .map_err(BinaryDiffError::IoError)?;
+                debug_assert_eq!(copied as usize, *length);
+                old.seek(SeekFrom::Start(position as u64))
+                    .map_err(BinaryDiffError::IoError)?;
+            }
+        }
+        position += chunk.original_length();
+    }
+
+    if position != old_size {
+        return Err(BinaryDiffError::IncompleteChunksError(position, old_size));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::apply_chunks;
+    use crate::binary_diff::binary_diff_chunk::BinaryDiffChunk::{
+        Copy, Delete, Insert, Replace, Same,
+    };
+    use crate::binary_diff::error::BinaryDiffError;
+    use std::io::{BufReader, Cursor};
+
+    #[test]
+    fn test_apply_chunks() {
+        let old = vec![0, 1, 2, 3, 4, 5];
+        let chunks = vec![
+            Same(0, 1),
+            Delete(1, 2),
+            Insert(3, vec![0xff]),
+            Same(3, 1),
+            Replace(4, 2, vec![0xaa, 0xbb, 0xcc]),
+        ];
+        let mut out = vec![];
+        let result = apply_chunks(&chunks, &mut BufReader::new(Cursor::new(&old)), &mut out);
+        assert!(result.is_ok(), "{:?}", result);
+        assert_eq!(out, vec![0, 0xff, 3, 0xaa, 0xbb, 0xcc]);
+    }
+
+    #[test]
+    fn test_apply_chunks_copy() {
+        let old = vec![0, 1, 2, 3];
+        let chunks = vec![
+            Same(0, 2),
+            Copy(2, 0, 2),
+            Delete(2, 1),
+            Copy(3, 1, 3),
+            Same(3, 1),
+        ];
+        let mut out = vec![];
+        let result = apply_chunks(&chunks, &mut BufReader::new(Cursor::new(&old)), &mut out);
+        assert!(result.is_ok(), "{:?}", result);
+        assert_eq!(out, vec![0, 1, 0, 1, 1, 2, 3, 3]);
+
+        let chunks = vec![Copy(0, 2, 3), Same(0, 4)];
+        match apply_chunks(&chunks, &mut BufReader::new(Cursor::new(&old)), &mut vec![]) {
+            Err(BinaryDiffError::ChunkOutOfRangeError(4, chunk)) => {
+                assert_eq!(chunk, Copy(0, 2, 3))
+            }
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_apply_chunks_offset_mismatch() {
+        let old = vec![0, 1, 2, 3];
+        let chunks = vec![Same(0, 1), Same(2, 2)];
+        let mut out = vec![];
+        match apply_chunks(&chunks, &mut BufReader::new(Cursor::new(&old)), &mut out) {
+            Err(BinaryDiffError::ChunkOffsetMismatchError(1, chunk)) => {
+                assert_eq!(chunk, Same(2, 2))
+            }
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_apply_chunks_out_of_range() {
+        let old = vec![0, 1, 2, 3];
+        let chunks = vec![Same(0, 2), Delete(2, 3)];
+        let mut out = vec![];
+        match apply_chunks(&chunks, &mut BufReader::new(Cursor::new(&old)), &mut out) {
+            Err(BinaryDiffError::ChunkOutOfRangeError(4, chunk)) => {
+                assert_eq!(chunk, Delete(2, 3))
+            }
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_apply_chunks_incomplete() {
+        let old = vec![0, 1, 2, 3];
+        let chunks = vec![Same(0, 2)];
+        let mut out = vec![];
+        match apply_chunks(&chunks, &mut BufReader::new(Cursor::new(&old)), &mut out) {
+            Err(BinaryDiffError::IncompleteChunksError(2, 4)) => (),
+            other => panic!("{:?}", other),
+        }
+    }
+}
@@ -0,0 +1,296 @@
+use crate::binary_diff::binary_diff_chunk::BinaryDiffChunk;
+use crate::binary_diff::error::BinaryDiffError;
+use crate::binary_diff::result::Result;
+use std::cmp::{max, min};
+
+// Fragments of intermediate binary in terms of original binary
+#[derive(Debug, Clone, Copy)]
+enum Piece<'a> {
+    // (offset in original binary, length): Bytes kept from original binary
+    Kept(usize, usize),
+    // Bytes introduced by the first diff
+    Added(&'a [u8]),
+    // (offset in original binary, length): Bytes removed by the first diff
+    Removed(usize, usize),
+    // (offset in original binary, length): Bytes copied from original binary by the first diff
+    Copied(usize, usize),
+}
+
+impl<'a> Piece<'a> {
+    // Returns how much bytes this piece occupies in intermediate binary
+    fn intermediate_length(&self) -> usize {
+        match self {
+            Piece::Kept(_, length) => *length,
+            Piece::Added(bytes) => bytes.len(),
+            Piece::Removed(_, _) => 0,
+            Piece::Copied(_, length) => *length,
+        }
+    }
+}
+
+struct Composer<'a> {
+    pieces: Vec<Piece<'a>>,
+    index: usize,
+    relative_position: usize,
+    original_position: usize,
+    chunks: Vec<BinaryDiffChunk>,
+}
+
+impl<'a> Composer<'a> {
+    fn new(first: &'a [BinaryDiffChunk]) -> Result<Self> {
+        let mut pieces = vec![];
+        let mut original_position = 0usize;
+        for chunk in first.iter() {
+            if *chunk.offset() != original_position {
+                return Err(BinaryDiffError::ChunkOffsetMismatchError(
+                    original_position,
+                    chunk.clone(),
+                ));
+            }
+            match chunk {
+                BinaryDiffChunk::Same(offset, length) => pieces.push(Piece::Kept(*offset, *length)),
+                BinaryDiffChunk::Insert(_, bytes) => pieces.push(Piece::Added(bytes)),
+                BinaryDiffChunk::Delete(offset, length) => {
+                    pieces.push(Piece::Removed(*offset, *length))
+                }
+                BinaryDiffChunk::Replace(offset, length, bytes) => {
+                    pieces.push(Piece::Removed(*offset, *length));
+                    pieces.push(Piece::Added(bytes));
+                }
+                BinaryDiffChunk::Copy(_, source, length) => {
+                    pieces.push(Piece::Copied(*source, *length))
+                }
+            }
+            original_position += chunk.original_length();
+        }
+
+        Ok(Self {
+            pieces,
+            index: 0,
+            relative_position: 0,
+            original_position: 0,
+            chunks: vec![],
+        })
+    }
+
+    fn intermediate_size(&self) -> usize {
+        self.pieces
+            .iter()
+            .map(|piece| piece.intermediate_length())
+            .sum()
+    }
+
+    // Bytes removed by the first diff are removed regardless of the second diff
+    fn flush_removed(&mut self) {
+        while let Some(Piece::Removed(offset, length)) = self.pieces.get(self.index) {
+            self.chunks.push(BinaryDiffChunk::Delete(*offset, *length));
+            self.original_position += length;
+            self.index += 1;
+        }
+    }
+
+    // Consumes `length` bytes of intermediate binary. Consumed bytes are kept if `keep`
+    fn consume(&mut self, mut length: usize, keep: bool) {
+        self.flush_removed();
+        while length > 0 {
+            let piece = match self.pieces.get(self.index) {
+                Some(piece) => *piece,
+                None => break,
+            };
+            let n = min(piece.intermediate_length() - self.relative_position, length);
+            match (piece, keep) {
+                (Piece::Kept(offset, _), true) => {
+                    self.chunks
+                        .push(BinaryDiffChunk::Same(offset + self.relative_position, n));
+                    self.original_position += n;
+                }
+                (Piece::Kept(offset, _), false) => {
+                    self.chunks
+                        .push(BinaryDiffChunk::Delete(offset + self.relative_position, n));
+                    self.original_position += n;
+                }
+                (Piece::Added(bytes), true) => self.chunks.push(BinaryDiffChunk::Insert(
+                    self.original_position,
+                    bytes[self.relative_position..self.relative_position + n].to_vec(),
+                )),
+                (Piece::Added(_), false) => (),
+                (Piece::Copied(source, _), true) => self.chunks.push(BinaryDiffChunk::Copy(
+                    self.original_position,
+                    source + self.relative_position,
+                    n,
+                )),
+                (Piece::Copied(_, _), false) => (),
+                (Piece::Removed(_, _), _) => unreachable!("Removed pieces must be flushed"),
+            }
+
+            self.relative_position += n;
+            length -= n;
+            if self.relative_position == piece.intermediate_length() {
+                self.index += 1;
+                self.relative_position = 0;
+                self.flush_removed();
+            }
+        }
+    }
+
+    // Inserts `length` bytes at `source` of intermediate binary without consuming them
+    fn copy(&mut self, source: usize, length: usize) {
+        self.flush_removed();
+        let mut start = 0usize;
+        for piece in self.pieces.iter() {
+            let end = start + piece.intermediate_length();
+            let (from, to) = (max(start, source), min(end, source + length));
+            if from < to {
+                let relative_position = from - start;
+                match piece {
+                    Piece::Kept(offset, _) | Piece::Copied(offset, _) => {
+                        self.chunks.push(BinaryDiffChunk::Copy(
+                            self.original_position,
+                            offset + relative_position,
+                            to - from,
+                        ))
+                    }
+                    Piece::Added(bytes) => self.chunks.push(BinaryDiffChunk::Insert(
+                        self.original_position,
+                        bytes[relative_position..relative_position + to - from].to_vec(),
+                    )),
+                    Piece::Removed(_, _) => unreachable!("Removed pieces have no length"),
+                }
+            }
+            start = end;
+        }
+    }
+
+    fn insert(&mut self, bytes: &[u8]) {
+        self.flush_removed();
+        self.chunks.push(BinaryDiffChunk::Insert(
+            self.original_position,
+            bytes.to_vec(),
+        ));
+    }
+}
+
+// compose_chunks() should satisfy following requirements:
+//   - Returned chunks transform original binary of `first` into patched binary of `second`
+//   - Patched binary of `first` must be original binary of `second`
+//   - Never reads any binaries
+// NOTE: Returned chunks are not merged. Apply merge_chunks() to normalize them.
+pub fn compose_chunks(
+    first: &[BinaryDiffChunk],
+    second: &[BinaryDiffChunk],
+) -> Result<Vec<BinaryDiffChunk>> {
+    let mut composer = Composer::new(first)?;
+    let intermediate_size = composer.intermediate_size();
+    log::trace!("[*] intermediate_size = {}", intermediate_size);
+
+    let mut intermediate_position = 0usize;
+    for chunk in second.iter() {
+        if *chunk.offset() != intermediate_position {
+            return Err(BinaryDiffError::ChunkOffsetMismatchError(
+                intermediate_position,
+                chunk.clone(),
+            ));
+        }
+        if intermediate_size - intermediate_position < chunk.original_length() {
+            return Err(BinaryDiffError::ChunkOutOfRangeError(
+                intermediate_size,
+                chunk.clone(),
+            ));
+        }
+
+        match chunk {
+            BinaryDiffChunk::Same(_, length) => composer.consume(*length, true),
+            BinaryDiffChunk::Insert(_, bytes) => composer.insert(bytes),
+            BinaryDiffChunk::Delete(_, length) => composer.consume(*length, false),
+            BinaryDiffChunk::Replace(_, length, bytes) => {
+                composer.consume(*length, false);
+                composer.insert(bytes);
+            }
+            BinaryDiffChunk::Copy(_, source, length) => {
+                if *source > intermediate_size || intermediate_size - source < *length {
+                    return Err(BinaryDiffError::ChunkOutOfRangeError(
+                        intermediate_size,
+                        chunk.clone(),
+                    ));
+                }
+                composer.copy(*source, *length)
+            }
+        }
+        intermediate_position += chunk.original_length();
+    }
+
+    if intermediate_position != intermediate_size {
+        return Err(BinaryDiffError::IncompleteChunksError(
+            intermediate_position,
+            intermediate_size,
+        ));
+    }
+    composer.flush_removed();
+
+    Ok(composer.chunks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::compose_chunks;
+    use crate::binary_diff::algorithm::merge_chunks;
+    use crate::binary_diff::binary_diff_chunk::BinaryDiffChunk::{
+        Copy, Delete, Insert, Replace, Same,
+    };
+
+    #[test]
+    fn test_compose_chunks() {
+        // [0 1 2 3 4 5] -> [0 3 aa bb cc] -> [0 dd bb cc]
+        let first = vec![
+            Same(0, 1),
+            Delete(1, 2),
+            Same(3, 1),
+            Replace(4, 2, vec![0xaa, 0xbb, 0xcc]),
+        ];
+        let second = vec![Same(0, 1), Replace(1, 2, vec![0xdd]), Same(3, 2)];
+        let composed = compose_chunks(&first, &second).unwrap();
+        assert_eq!(
+            merge_chunks(&composed),
+            vec![Same(0, 1), Delete(1, 5), Insert(6, vec![0xdd, 0xbb, 0xcc])]
+        );
+    }
+
+    #[test]
+    fn test_compose_chunks_insert_then_delete() {
+        // [0 1] -> [0 ff 1] -> [0 1]
+        let first = vec![Same(0, 1), Insert(1, vec![0xff]), Same(1, 1)];
+        let second = vec![Same(0, 1), Delete(1, 1), Same(2, 1)];
+        let composed = compose_chunks(&first, &second).unwrap();
+        assert_eq!(merge_chunks(&composed), vec![Same(0, 2)]);
+    }
+
+    #[test]
+    fn test_compose_chunks_copy() {
+        // [0 1 2 3] -> [0 1 aa 2 3 0 1] -> [0 1 aa 2 3 0 1 aa 2 0 1]
+        let first = vec![Same(0, 2), Insert(2, vec![0xaa]), Same(2, 2), Copy(4, 0, 2)];
+        let second = vec![Same(0, 7), Copy(7, 1, 3), Copy(7, 5, 2)];
+        let composed = compose_chunks(&first, &second).unwrap();
+        assert_eq!(
+            merge_chunks(&composed),
+            vec![
+                Same(0, 2),
+                Insert(2, vec![0xaa]),
+                Same(2, 2),
+                Copy(4, 0, 2),
+                Copy(4, 1, 1),
+                Insert(4, vec![0xaa]),
+                Copy(4, 2, 1),
+                Copy(4, 0, 2),
+            ]
+        );
+
+        assert!(compose_chunks(&first, &[Same(0, 7), Copy(7, 6, 2)]).is_err());
+    }
+
+    #[test]
+    fn test_compose_chunks_size_mismatch() {
+        let first = vec![Same(0, 2)];
+        assert!(compose_chunks(&first, &[Same(0, 3)]).is_err());
+        assert!(compose_chunks(&first, &[Same(0, 1)]).is_err());
+    }
+}
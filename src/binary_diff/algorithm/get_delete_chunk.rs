@@ -1,19 +1,21 @@
 use super::super::binary_diff_chunk::BinaryDiffChunk;
+use super::super::diff_options::{DiffOptions, TieBreaking};
 use super::super::error::BinaryDiffError;
 use super::super::helper::find;
+use super::super::helper::find_longest_common_substring;
 use super::super::helper::read_bytes;
 use super::super::result::Result;
-use bcmp::{longest_common_substring, AlgoSpec};
 use std::cmp::min;
 use std::io::{BufReader, Read, Seek, SeekFrom};
 
 // get_delete_chunk() should satisfy following requirements:
 //   - Minimize `length` of Delete(offset, length)
-pub fn get_delete_chunk<R: Read + Seek>(
-    old: &mut BufReader<R>,
-    new: &mut BufReader<R>,
+pub fn get_delete_chunk<R1: Read + Seek, R2: Read + Seek>(
+    old: &mut BufReader<R1>,
+    new: &mut BufReader<R2>,
     old_size: usize,
     new_size: usize,
+    options: &DiffOptions,
 ) -> Result<Option<BinaryDiffChunk>> {
     let offset = old.stream_position().map_err(BinaryDiffError::IoError)? as usize;
 
@@ -36,8 +38,11 @@ pub fn get_delete_chunk<R: Read + Seek>(
     log::trace!("offset = {}, N = {}", offset, N);
 
     if N > 0 {
-        // NOTE: More values of the window are fine grained, more Delete() chunk become precise.
-        for window in [6, 8, 16, 32, 64] {
+        for window in options.delete_windows.iter() {
+            let window = min(*window, options.max_lookahead);
+            if window == 0 {
+                continue;
+            }
             log::trace!("window = {}", window);
             // Find offset that minimizes `offset` of next Same(offset, length)
 
@@ -58,16 +63,38 @@ pub fn get_delete_chunk<R: Read + Seek>(
 
             if window >= 4 {
                 // Algorithm (1): For wide window
-                let lcs = longest_common_substring(
+                let lcs = find_longest_common_substring(
                     old_bytes.as_slice(),
                     new_bytes.as_slice(),
-                    AlgoSpec::HashMatch(if window <= 16 { 1 } else { 2 }),
+                    options.hash_size(window, min(old_bytes.len(), new_bytes.len())),
                 );
                 log::trace!("old_bytes = {:?}", old_bytes);
                 log::trace!("new_bytes = {:?}", new_bytes);
                 log::trace!("lcs = {:?}", lcs);
 
-                if lcs.length > 0 {
+                if lcs.length > 0 && lcs.length >= options.min_match_length {
+                    // old: [ a b c d ]
+                    //        ~ Delete a, or Insert b c d if preferred
+                    // new: [ b c d a b c d ]
+                    if lcs.first_pos > 0
+                        && lcs.second_pos == 0
+                        && options.tie_breaking == TieBreaking::PreferInsert
+                    {
+                        if let Some(inserted_length) = find_insert_alternative(
+                            &old_bytes,
+                            &new_bytes,
+                            lcs.length,
+                            options.min_match_length,
+                        ) {
+                            new.seek_relative(inserted_length as i64)
+                                .map_err(BinaryDiffError::IoError)?;
+                            return Ok(Some(BinaryDiffChunk::Insert(
+                                offset,
+                                new_bytes[0..inserted_length].to_vec(),
+                            )));
+                        }
+                    }
+
                     return if lcs.first_pos > 0 {
                         old.seek_relative(lcs.first_pos as i64)
                             .map_err(BinaryDiffError::IoError)?;
@@ -102,7 +129,7 @@ pub fn get_delete_chunk<R: Read + Seek>(
                             Some(v) => v > &0,
                             None => false,
                         })
-                        .min_by_key(|(_, v)| v.clone());
+                        .min_by_key(|(_, v)| *v);
 
                     // old: [ a b c ]
                     //        ~ Delete a & No insert
@@ -112,31 +139,41 @@ pub fn get_delete_chunk<R: Read + Seek>(
                             Some(v) => v == &0,
                             None => false,
                         })
-                        .min_by_key(|(k, _)| k.clone());
+                        .min_by_key(|(k, _)| *k);
 
                     // Determine next chunk by checking
                     // which next possible Insert() or Same() chunk is CLOSED to current Delete() chunk.
-                    match (allowing_insert_chunk, disallowing_insert_chunk) {
-                        // To decrease number of Insert() chunk
-                        (_, Some((offset_if_disallowed, _))) => Some(offset_if_disallowed),
-                        (Some((offset_if_allowed, _)), None) => Some(offset_if_allowed),
-                        (None, None) => None,
+                    match (
+                        allowing_insert_chunk,
+                        disallowing_insert_chunk,
+                        options.tie_breaking,
+                    ) {
+                        (Some(allowed), Some(_), TieBreaking::PreferInsert) => Some(allowed),
+                        (_, Some(disallowed), _) => Some(disallowed),
+                        (Some(allowed), None, _) => Some(allowed),
+                        (None, None, _) => None,
                     }
                 };
                 log::trace!("next_same_offset = {:?}", next_same_offset);
 
-                match next_same_offset {
-                    Some(next_same_offset) => {
-                        return if next_same_offset > 0 {
-                            old.seek_relative(next_same_offset as i64)
-                                .map_err(BinaryDiffError::IoError)?;
-                            Ok(Some(BinaryDiffChunk::Delete(offset, next_same_offset)))
-                        } else {
-                            // Next chunk is Insert(offset, new_bytes[0..min(find(...))])
-                            Ok(None)
-                        };
-                    }
-                    None => (), // Continue loop to check next window
+                // Otherwise continue loop to check next window
+                if let Some((next_same_offset, next_same_new_offset)) = next_same_offset {
+                    let next_same_new_offset = next_same_new_offset.unwrap_or(0);
+                    return if next_same_offset > 0 {
+                        old.seek_relative(next_same_offset as i64)
+                            .map_err(BinaryDiffError::IoError)?;
+                        Ok(Some(BinaryDiffChunk::Delete(offset, next_same_offset)))
+                    } else if next_same_new_offset > 0 {
+                        // Nothing to be deleted
+                        new.seek_relative(next_same_new_offset as i64)
+                            .map_err(BinaryDiffError::IoError)?;
+                        Ok(Some(BinaryDiffChunk::Insert(
+                            offset,
+                            new_bytes[0..next_same_new_offset].to_vec(),
+                        )))
+                    } else {
+                        Ok(None)
+                    };
                 }
             }
         }
@@ -161,3 +198,23 @@ pub fn get_delete_chunk<R: Read + Seek>(
         Ok(None)
     }
 }
+
+// Returns length of Insert() chunk that can be chosen instead of Delete() chunk, i.e. the nearest offset in `new_bytes`
+// where `old_bytes` matches as long as `length`, or matches until the end of `new_bytes`
+fn find_insert_alternative(
+    old_bytes: &[u8],
+    new_bytes: &[u8],
+    length: usize,
+    min_match_length: usize,
+) -> Option<usize> {
+    (1..new_bytes.len()).find(|inserted_length| {
+        let matched_length = old_bytes
+            .iter()
+            .zip(new_bytes[*inserted_length..].iter())
+            .take_while(|(old_byte, new_byte)| old_byte == new_byte)
+            .count();
+        matched_length >= length
+            || (matched_length >= min_match_length.max(1)
+                && inserted_length + matched_length == new_bytes.len())
+    })
+}
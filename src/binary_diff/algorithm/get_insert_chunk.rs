@@ -1,18 +1,20 @@
 use super::super::binary_diff_chunk::BinaryDiffChunk;
+use super::super::diff_options::DiffOptions;
 use super::super::error::BinaryDiffError;
+use super::super::helper::find_longest_common_substring;
 use super::super::helper::read_bytes;
 use super::super::result::Result;
-use bcmp::{longest_common_substring, AlgoSpec};
 use std::cmp::min;
 use std::io::{BufReader, Read, Seek};
 
 // get_insert_chunk() should satisfy following requirements:
 //   - Maximize length of `bytes` of Insert(offset, bytes)
-pub fn get_insert_chunk<R: Read + Seek>(
-    old: &mut BufReader<R>,
-    new: &mut BufReader<R>,
+pub fn get_insert_chunk<R1: Read + Seek, R2: Read + Seek>(
+    old: &mut BufReader<R1>,
+    new: &mut BufReader<R2>,
     old_size: usize,
     new_size: usize,
+    options: &DiffOptions,
 ) -> Result<Option<BinaryDiffChunk>> {
     let offset = old.stream_position().map_err(BinaryDiffError::IoError)? as usize;
     #[allow(non_snake_case)]
@@ -24,15 +26,18 @@ pub fn get_insert_chunk<R: Read + Seek>(
     }
 
     if offset < old_size {
-        // NOTE: window=2 is required to detect Insert(offset, length=1) chunk
-        for window in [4, 8, 16, 32, 2] {
+        for window in options.insert_windows.iter() {
+            let window = min(*window, options.max_lookahead);
+            if window == 0 {
+                continue;
+            }
             let old_bytes = read_bytes(old, min(window, old_size - offset))?;
             let new_bytes = read_bytes(new, min(window, N))?;
 
-            let lcs = longest_common_substring(
+            let lcs = find_longest_common_substring(
                 old_bytes.as_slice(),
                 new_bytes.as_slice(),
-                AlgoSpec::HashMatch(if window <= 16 { 1 } else { 2 }),
+                options.hash_size(window, min(old_bytes.len(), new_bytes.len())),
             );
             log::trace!("old_bytes = {:?}", old_bytes);
             log::trace!("new_bytes = {:?}", new_bytes);
@@ -44,7 +49,7 @@ pub fn get_insert_chunk<R: Read + Seek>(
             new.seek_relative(-(new_bytes.len() as i64))
                 .map_err(BinaryDiffError::IoError)?;
 
-            if lcs.length > 0 {
+            if lcs.length > 0 && lcs.length >= options.min_match_length {
                 return if lcs.second_pos > 0 {
                     new.seek_relative(lcs.second_pos as i64)
                         .map_err(BinaryDiffError::IoError)?;
@@ -67,9 +72,8 @@ pub fn get_insert_chunk<R: Read + Seek>(
         for i in 0..N {
             let new_byte = read_bytes(new, 1)?;
             if new_byte == old_byte {
+                new.seek_relative(-1).map_err(BinaryDiffError::IoError)?;
                 return if i > 0 {
-                    new.seek_relative(-1)
-                        .map_err(BinaryDiffError::IoError)?;
                     Ok(Some(BinaryDiffChunk::Insert(offset, bytes)))
                 } else {
                     // Should generate same chunk
@@ -86,7 +90,7 @@ pub fn get_insert_chunk<R: Read + Seek>(
         let mut inserted_bytes = vec![];
         new.read_to_end(&mut inserted_bytes)
             .map_err(BinaryDiffError::IoError)?;
-        if inserted_bytes.len() > 0 {
+        if !inserted_bytes.is_empty() {
             Ok(Some(BinaryDiffChunk::Insert(offset, inserted_bytes)))
         } else {
             // inserted_bytes.len() must be larger than 0 since N > 0, but fail safe
@@ -0,0 +1,270 @@
+use super::merge_chunks;
+use crate::binary_diff::binary_diff_chunk::BinaryDiffChunk;
+
+enum Task {
+    // (old_start, old_end, new_start, new_end): Diff old[old_start..old_end] and new[new_start..new_end]
+    Diff(usize, usize, usize, usize),
+    // (offset, length): Same bytes found by bisect()
+    Same(usize, usize),
+}
+
+// Finds the middle snake of Myers' algorithm.
+// Returns the point to split `old` and `new`, or None if they have nothing in common.
+fn bisect(old: &[u8], new: &[u8]) -> Option<(usize, usize)> {
+    let (old_len, new_len) = (old.len() as isize, new.len() as isize);
+    let max_d = (old_len + new_len + 1) / 2;
+    let v_offset = max_d;
+    let v_length = 2 * max_d + 2;
+    let mut v1 = vec![-1isize; v_length as usize];
+    let mut v2 = vec![-1isize; v_length as usize];
+    v1[(v_offset + 1) as usize] = 0;
+    v2[(v_offset + 1) as usize] = 0;
+
+    let delta = old_len - new_len;
+    // If the total number of bytes is odd, then the front path will collide with the reverse path
+    let front = delta % 2 != 0;
+    // Offsets for start and end of k loop to prevent mapping of space beyond the grid
+    let (mut k1_start, mut k1_end, mut k2_start, mut k2_end) = (0isize, 0isize, 0isize, 0isize);
+
+    for d in 0..max_d {
+        // Walk the front path one step
+        let mut k1 = -d + k1_start;
+        while k1 <= d - k1_end {
+            let k1_offset = (v_offset + k1) as usize;
+            let mut x1 = if k1 == -d || (k1 != d && v1[k1_offset - 1] < v1[k1_offset + 1]) {
+                v1[k1_offset + 1]
+            } else {
+                v1[k1_offset - 1] + 1
+            };
+            let mut y1 = x1 - k1;
+            while x1 < old_len && y1 < new_len && old[x1 as usize] == new[y1 as usize] {
+                x1 += 1;
+                y1 += 1;
+            }
+            v1[k1_offset] = x1;
+            if x1 > old_len {
+                // Ran off the right of the graph
+                k1_end += 2;
+            } else if y1 > new_len {
+                // Ran off the bottom of the graph
+                k1_start += 2;
+            } else if front {
+                let k2_offset = v_offset + delta - k1;
+                if k2_offset >= 0 && k2_offset < v_length && v2[k2_offset as usize] != -1 {
+                    // Mirror x2 onto top-left coordinate system
+                    let x2 = old_len - v2[k2_offset as usize];
+                    if x1 >= x2 {
+                        return Some((x1 as usize, y1 as usize));
+                    }
+                }
+            }
+            k1 += 2;
+        }
+
+        // Walk the reverse path one step
+        let mut k2 = -d + k2_start;
+        while k2 <= d - k2_end {
+            let k2_offset = (v_offset + k2) as usize;
+            let mut x2 = if k2 == -d || (k2 != d && v2[k2_offset - 1] < v2[k2_offset + 1]) {
+                v2[k2_offset + 1]
+            } else {
+                v2[k2_offset - 1] + 1
+            };
+            let mut y2 = x2 - k2;
+            while x2 < old_len
+                && y2 < new_len
+                && old[(old_len - x2 - 1) as usize] == new[(new_len - y2 - 1) as usize]
+            {
+                x2 += 1;
+                y2 += 1;
+            }
+            v2[k2_offset] = x2;
+            if x2 > old_len {
+                k2_end += 2;
+            } else if y2 > new_len {
+                k2_start += 2;
+            } else if !front {
+                let k1_offset = v_offset + delta - k2;
+                if k1_offset >= 0 && k1_offset < v_length && v1[k1_offset as usize] != -1 {
+                    let x1 = v1[k1_offset as usize];
+                    let y1 = v_offset + x1 - k1_offset;
+                    // Mirror x2 onto top-left coordinate system
+                    if x1 >= old_len - x2 {
+                        return Some((x1 as usize, y1 as usize));
+                    }
+                }
+            }
+            k2 += 2;
+        }
+    }
+
+    None
+}
+
+// get_myers_chunks() should satisfy following requirements:
+//   - Minimize total length of Delete() and Insert() chunks (i.e. minimal edit script)
+//   - Changes between two Same() chunks are merged into one Delete() chunk followed by one Insert() chunk
+// NOTE: Uses linear space variant of Myers' O(ND) algorithm. Running time grows rapidly with the number of changes.
+pub fn get_myers_chunks(old: &[u8], new: &[u8]) -> Vec<BinaryDiffChunk> {
+    let mut chunks = vec![];
+    // Tasks are processed in LIFO order, so push the latter part first
+    let mut tasks = vec![Task::Diff(0, old.len(), 0, new.len())];
+
+    while let Some(task) = tasks.pop() {
+        let (mut old_start, mut old_end, mut new_start, mut new_end) = match task {
+            Task::Same(offset, length) => {
+                chunks.push(BinaryDiffChunk::Same(offset, length));
+                continue;
+            }
+            Task::Diff(old_start, old_end, new_start, new_end) => {
+                (old_start, old_end, new_start, new_end)
+            }
+        };
+
+        // Trim common prefix
+        let prefix_length = old[old_start..old_end]
+            .iter()
+            .zip(new[new_start..new_end].iter())
+            .take_while(|(a, b)| a == b)
+            .count();
+        if prefix_length > 0 {
+            chunks.push(BinaryDiffChunk::Same(old_start, prefix_length));
+            old_start += prefix_length;
+            new_start += prefix_length;
+        }
+
+        // Trim common suffix
+        let suffix_length = old[old_start..old_end]
+            .iter()
+            .rev()
+            .zip(new[new_start..new_end].iter().rev())
+            .take_while(|(a, b)| a == b)
+            .count();
+        if suffix_length > 0 {
+            old_end -= suffix_length;
+            new_end -= suffix_length;
+            tasks.push(Task::Same(old_end, suffix_length));
+        }
+
+        if old_start == old_end || new_start == new_end {
+            chunks.push(BinaryDiffChunk::Delete(old_start, old_end - old_start));
+            chunks.push(BinaryDiffChunk::Insert(
+                old_end,
+                new[new_start..new_end].to_vec(),
+            ));
+            continue;
+        }
+
+        match bisect(&old[old_start..old_end], &new[new_start..new_end]) {
+            Some((x, y)) => {
+                tasks.push(Task::Diff(old_start + x, old_end, new_start + y, new_end));
+                tasks.push(Task::Diff(
+                    old_start,
+                    old_start + x,
+                    new_start,
+                    new_start + y,
+                ));
+            }
+            None => {
+                chunks.push(BinaryDiffChunk::Delete(old_start, old_end - old_start));
+                chunks.push(BinaryDiffChunk::Insert(
+                    old_end,
+                    new[new_start..new_end].to_vec(),
+                ));
+            }
+        }
+    }
+
+    merge_chunks(&chunks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::get_myers_chunks;
+    use crate::binary_diff::algorithm::apply_chunks;
+    use crate::binary_diff::binary_diff_chunk::BinaryDiffChunk::{Delete, Insert, Same};
+    use crate::test_helper::random_bytes;
+    use std::io::{BufReader, Cursor};
+
+    #[test]
+    fn test_get_myers_chunks() {
+        let old = vec![0x00, 0x0b, 0x01, 0x00, 0x03, 0xfe, 0x00, 0x03];
+        let new = vec![0x00, 0x0b, 0x01, 0xfd, 0x03, 0xfe, 0x00, 0x03];
+        assert_eq!(
+            get_myers_chunks(&old, &new),
+            vec![Same(0, 3), Delete(3, 1), Insert(4, vec![0xfd]), Same(4, 4)]
+        );
+    }
+
+    #[test]
+    fn test_get_myers_chunks_empty() {
+        assert_eq!(get_myers_chunks(&[], &[]), vec![]);
+        assert_eq!(get_myers_chunks(&[0, 1], &[]), vec![Delete(0, 2)]);
+        assert_eq!(get_myers_chunks(&[], &[0, 1]), vec![Insert(0, vec![0, 1])]);
+    }
+
+    #[test]
+    fn test_get_myers_chunks_crash_minimization() {
+        let old = vec![
+            0x5c, 0x53, 0x3f, 0x5c, 0x43, 0x5c, 0x53, 0x3f, 0x5c, 0x43, 0xd5, 0xac, 0x32, 0x2a,
+            0xd5, 0xac, 0x43, 0x5c, 0x53, 0x16,
+        ];
+        let new = vec![0x5c, 0x43, 0x5c, 0x53, 0x3f, 0xd5, 0xac, 0x16, 0x5c, 0x16];
+        let chunks = get_myers_chunks(&old, &new);
+        let (deleted, inserted) = chunks.iter().fold((0, 0), |(d, i), chunk| match chunk {
+            Delete(_, length) => (d + length, i),
+            Insert(_, bytes) => (d, i + bytes.len()),
+            _ => (d, i),
+        });
+        // Longest common subsequence of `old` and `new` is 9 bytes long
+        assert_eq!((deleted, inserted), (old.len() - 9, new.len() - 9));
+    }
+
+    #[test]
+    fn test_get_myers_chunks_is_minimal() {
+        fn lcs_length(a: &[u8], b: &[u8]) -> usize {
+            let mut table = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+            for i in 0..a.len() {
+                for j in 0..b.len() {
+                    table[i + 1][j + 1] = if a[i] == b[j] {
+                        table[i][j] + 1
+                    } else {
+                        std::cmp::max(table[i][j + 1], table[i + 1][j])
+                    };
+                }
+            }
+            table[a.len()][b.len()]
+        }
+
+        let mut seed = 0x1234_5678u32;
+        // Small alphabet to make common bytes
+        let mut random_small_bytes = |length: usize| -> Vec<u8> {
+            random_bytes(&mut seed, length)
+                .iter()
+                .map(|byte| byte % 4)
+                .collect()
+        };
+        for i in 0..200 {
+            let old = random_small_bytes(i % 23);
+            let new = random_small_bytes(i % 17);
+            let chunks = get_myers_chunks(&old, &new);
+            let same_length: usize = chunks
+                .iter()
+                .map(|chunk| match chunk {
+                    Same(_, length) => *length,
+                    _ => 0,
+                })
+                .sum();
+            assert_eq!(same_length, lcs_length(&old, &new), "{:?}", chunks);
+
+            let mut patched = vec![];
+            apply_chunks(
+                &chunks,
+                &mut BufReader::new(Cursor::new(&old)),
+                &mut patched,
+            )
+            .unwrap();
+            assert_eq!(patched, new);
+        }
+    }
+}
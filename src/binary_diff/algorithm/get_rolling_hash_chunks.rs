@@ -0,0 +1,337 @@
+use super::merge_chunks;
+use crate::binary_diff::binary_diff_chunk::BinaryDiffChunk;
+use crate::binary_diff::diff_algorithm::DiffAlgorithm;
+use crate::binary_diff::error::BinaryDiffError;
+use crate::binary_diff::helper::{get_buffer_length, read_bytes};
+use crate::binary_diff::result::Result;
+use std::cmp::max;
+use std::collections::{HashMap, VecDeque};
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::ops::Range;
+
+// Blocks of original binary to be indexed at most. Blocks become larger for larger binary to bound memory usage
+const MAX_BLOCK_COUNT: usize = 0x100000;
+// Changes between matched blocks larger than this value are not refined
+const MAX_REFINEMENT_SIZE: usize = 0x10000;
+
+// Weak checksum of rsync that can be rolled by one byte
+#[derive(Debug, Clone, Copy, Default)]
+struct RollingChecksum {
+    a: u32,
+    b: u32,
+    length: u32,
+}
+
+impl RollingChecksum {
+    fn push(&mut self, byte: u8) {
+        self.a = self.a.wrapping_add(byte as u32);
+        self.b = self.b.wrapping_add(self.a);
+        self.length += 1;
+    }
+
+    fn roll(&mut self, removed: u8, added: u8) {
+        self.a = self
+            .a
+            .wrapping_sub(removed as u32)
+            .wrapping_add(added as u32);
+        self.b = self
+            .b
+            .wrapping_sub(self.length.wrapping_mul(removed as u32))
+            .wrapping_add(self.a);
+    }
+
+    fn digest(&self) -> u32 {
+        (self.a & 0xffff) | (self.b << 16)
+    }
+}
+
+struct BlockIndex {
+    block_size: usize,
+    // Offsets of blocks in ascending order for each checksum
+    offsets: HashMap<u32, Vec<usize>>,
+    // Filters out most of checksums not in `offsets` without hashing
+    tags: Vec<bool>,
+}
+
+impl BlockIndex {
+    fn new<R: Read + Seek>(
+        old: &mut BufReader<R>,
+        old_size: usize,
+        block_size: usize,
+    ) -> Result<Self> {
+        let mut index = Self {
+            block_size,
+            offsets: HashMap::new(),
+            tags: vec![false; 0x10000],
+        };
+        old.seek(SeekFrom::Start(0))
+            .map_err(BinaryDiffError::IoError)?;
+        for i in 0..old_size / block_size {
+            let mut checksum = RollingChecksum::default();
+            for byte in read_bytes(old, block_size)?.into_iter() {
+                checksum.push(byte);
+            }
+            index.tags[Self::tag(checksum.digest())] = true;
+            index
+                .offsets
+                .entry(checksum.digest())
+                .or_default()
+                .push(i * block_size);
+        }
+        Ok(index)
+    }
+
+    fn tag(digest: u32) -> usize {
+        ((digest ^ (digest >> 16)) & 0xffff) as usize
+    }
+
+    // Returns offset of block equal to `window`. Prefers the first one at or after `position`
+    fn find<R: Read + Seek>(
+        &self,
+        old: &mut BufReader<R>,
+        window: &VecDeque<u8>,
+        checksum: &RollingChecksum,
+        position: usize,
+    ) -> Result<Option<usize>> {
+        if !self.tags[Self::tag(checksum.digest())] {
+            return Ok(None);
+        }
+        let offsets = match self.offsets.get(&checksum.digest()) {
+            Some(offsets) => offsets,
+            None => return Ok(None),
+        };
+
+        // Checksum may collide, so compare bytes
+        let i = offsets.partition_point(|offset| *offset < position);
+        for offset in [offsets.get(i), offsets.first()].iter().flatten() {
+            old.seek(SeekFrom::Start(**offset as u64))
+                .map_err(BinaryDiffError::IoError)?;
+            if window.iter().eq(read_bytes(old, self.block_size)?.iter()) {
+                return Ok(Some(**offset));
+            }
+        }
+        Ok(None)
+    }
+}
+
+// Pushes chunks that transform old[range] into `literals`.
+// `flushed` tells that some literals before `literals` are already pushed, so they are not refined.
+fn push_refined_chunks<R: Read + Seek, A: DiffAlgorithm>(
+    chunks: &mut Vec<BinaryDiffChunk>,
+    old: &mut BufReader<R>,
+    range: Range<usize>,
+    literals: Vec<u8>,
+    flushed: bool,
+    refinement: &A,
+) -> Result<()> {
+    let (offset, length) = (range.start, range.end - range.start);
+    if length == 0
+        || literals.is_empty()
+        || flushed
+        || length > MAX_REFINEMENT_SIZE
+        || literals.len() > MAX_REFINEMENT_SIZE
+    {
+        chunks.push(BinaryDiffChunk::Delete(offset, length));
+        chunks.push(BinaryDiffChunk::Insert(range.end, literals));
+        return Ok(());
+    }
+
+    old.seek(SeekFrom::Start(offset as u64))
+        .map_err(BinaryDiffError::IoError)?;
+    let old_bytes = read_bytes(old, length)?;
+    for chunk in refinement.diff_slices(&old_bytes, &literals)?.into_iter() {
+        chunks.push(match chunk {
+            BinaryDiffChunk::Same(o, l) => BinaryDiffChunk::Same(offset + o, l),
+            BinaryDiffChunk::Insert(o, b) => BinaryDiffChunk::Insert(offset + o, b),
+            BinaryDiffChunk::Delete(o, l) => BinaryDiffChunk::Delete(offset + o, l),
+            BinaryDiffChunk::Replace(o, l, b) => BinaryDiffChunk::Replace(offset + o, l, b),
+            BinaryDiffChunk::Copy(o, s, l) => BinaryDiffChunk::Copy(offset + o, offset + s, l),
+        });
+    }
+    Ok(())
+}
+
+// get_rolling_hash_chunks() should satisfy following requirements:
+//   - Reads `new` only once from the start, and never holds whole of `old` and `new` on memory
+//   - Blocks of `old` found in `new` become Same() chunks, or Copy() chunks if found out of order
+//   - Changes between blocks are refined by `refinement` if they are small enough
+// NOTE: Blocks of `old` are aligned to `block_size`, like rsync.
+pub fn get_rolling_hash_chunks<R1: Read + Seek, R2: Read + Seek, A: DiffAlgorithm>(
+    old: &mut BufReader<R1>,
+    new: &mut BufReader<R2>,
+    block_size: usize,
+    refinement: &A,
+) -> Result<Vec<BinaryDiffChunk>> {
+    let old_size = get_buffer_length(old)?;
+    let block_size = max(max(block_size, 1), old_size.div_ceil(MAX_BLOCK_COUNT));
+    log::trace!("[*] old_size, block_size = {}, {}", old_size, block_size);
+    let index = BlockIndex::new(old, old_size, block_size)?;
+
+    let mut chunks = vec![];
+    let mut old_position = 0usize;
+    // Bytes of `new` not found in `old`
+    let mut literals = vec![];
+    // Whether literals since the last matched block have been pushed as Insert() chunk
+    let mut flushed = false;
+    let mut window = VecDeque::with_capacity(block_size);
+    let mut checksum = RollingChecksum::default();
+
+    new.seek(SeekFrom::Start(0))
+        .map_err(BinaryDiffError::IoError)?;
+    let mut new_bytes = new.by_ref().bytes();
+    loop {
+        if window.len() == block_size {
+            if let Some(offset) = index.find(old, &window, &checksum, old_position)? {
+                let literals = std::mem::take(&mut literals);
+                if offset >= old_position {
+                    push_refined_chunks(
+                        &mut chunks,
+                        old,
+                        old_position..offset,
+                        literals,
+                        flushed,
+                        refinement,
+                    )?;
+                    chunks.push(BinaryDiffChunk::Same(offset, block_size));
+                    old_position = offset + block_size;
+                } else {
+                    chunks.push(BinaryDiffChunk::Insert(old_position, literals));
+                    chunks.push(BinaryDiffChunk::Copy(old_position, offset, block_size));
+                }
+                flushed = false;
+                window.clear();
+                checksum = RollingChecksum::default();
+                continue;
+            }
+        }
+
+        let byte = match new_bytes.next() {
+            Some(byte) => byte.map_err(BinaryDiffError::IoError)?,
+            None => break,
+        };
+        if window.len() == block_size {
+            if let Some(removed) = window.pop_front() {
+                literals.push(removed);
+                checksum.roll(removed, byte);
+            }
+            // Too many literals are not refined, so they are not kept until next block is found
+            if literals.len() > MAX_REFINEMENT_SIZE {
+                chunks.push(BinaryDiffChunk::Insert(
+                    old_position,
+                    std::mem::take(&mut literals),
+                ));
+                flushed = true;
+            }
+        } else {
+            checksum.push(byte);
+        }
+        window.push_back(byte);
+    }
+
+    literals.extend(window);
+    push_refined_chunks(
+        &mut chunks,
+        old,
+        old_position..old_size,
+        literals,
+        flushed,
+        refinement,
+    )?;
+
+    Ok(merge_chunks(&chunks))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{get_rolling_hash_chunks, RollingChecksum};
+    use crate::binary_diff::algorithm::apply_chunks;
+    use crate::binary_diff::binary_diff_chunk::BinaryDiffChunk;
+    use crate::binary_diff::binary_diff_chunk::BinaryDiffChunk::{Copy, Delete, Insert, Same};
+    use crate::binary_diff::diff_algorithm::GreedyAlgorithm;
+    use crate::test_helper::random_bytes;
+    use std::io::{BufReader, Cursor};
+
+    fn diff(old: &[u8], new: &[u8], block_size: usize) -> Vec<BinaryDiffChunk> {
+        let chunks = get_rolling_hash_chunks(
+            &mut BufReader::new(Cursor::new(old)),
+            &mut BufReader::new(Cursor::new(new)),
+            block_size,
+            &GreedyAlgorithm::default(),
+        )
+        .unwrap();
+
+        let mut patched = vec![];
+        apply_chunks(&chunks, &mut BufReader::new(Cursor::new(old)), &mut patched).unwrap();
+        assert_eq!(patched, new);
+        chunks
+    }
+
+    #[test]
+    fn test_rolling_checksum() {
+        let bytes = [1, 2, 3, 4, 5];
+        let mut rolled = RollingChecksum::default();
+        bytes[0..3].iter().for_each(|byte| rolled.push(*byte));
+        rolled.roll(1, 4);
+        rolled.roll(2, 5);
+
+        let mut expected = RollingChecksum::default();
+        bytes[2..5].iter().for_each(|byte| expected.push(*byte));
+        assert_eq!(rolled.digest(), expected.digest());
+    }
+
+    #[test]
+    fn test_get_rolling_hash_chunks() {
+        assert_eq!(diff(&[], &[], 4), vec![]);
+        assert_eq!(diff(&[0, 1], &[], 4), vec![Delete(0, 2)]);
+        assert_eq!(diff(&[], &[0, 1], 4), vec![Insert(0, vec![0, 1])]);
+
+        let mut seed = 0x1234_5678;
+        let old = random_bytes(&mut seed, 0x1000);
+        assert_eq!(diff(&old, &old, 0x100), vec![Same(0, 0x1000)]);
+    }
+
+    #[test]
+    fn test_get_rolling_hash_chunks_refinement() {
+        let mut seed = 0x1234_5678;
+        let old = random_bytes(&mut seed, 0x1000);
+        let mut new = old.clone();
+        new[0x234] = !new[0x234];
+        new.insert(0x800, 0xff);
+
+        assert_eq!(
+            diff(&old, &new, 0x100),
+            vec![
+                Same(0, 0x234),
+                Delete(0x234, 1),
+                Insert(0x235, vec![!old[0x234]]),
+                Same(0x235, 0x5cb),
+                Insert(0x800, vec![0xff]),
+                Same(0x800, 0x800),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_get_rolling_hash_chunks_relocation() {
+        let mut seed = 0x1234_5678;
+        let old = random_bytes(&mut seed, 0x1000);
+        let new = [&old[0x400..0x1000], &old[0..0x400]].concat();
+
+        assert_eq!(
+            diff(&old, &new, 0x100),
+            vec![Delete(0, 0x400), Same(0x400, 0xc00), Copy(0x1000, 0, 0x400)]
+        );
+    }
+
+    #[test]
+    fn test_get_rolling_hash_chunks_dissimilar() {
+        let mut seed = 0x1234_5678;
+        let old = random_bytes(&mut seed, 0x1000);
+        let new = random_bytes(&mut seed, 0x28000);
+
+        assert_eq!(
+            diff(&old, &new, 0x100),
+            vec![Delete(0, 0x1000), Insert(0x1000, new)]
+        );
+    }
+}
@@ -7,9 +7,9 @@ use crate::binary_diff::helper::read_one_byte;
 
 // get_same_chunk() should satisfy following requirements:
 //   - Maximize `length` of Same(offset, length)
-pub fn get_same_chunk<R: Read + Seek>(
-    old: &mut BufReader<R>,
-    new: &mut BufReader<R>,
+pub fn get_same_chunk<R1: Read + Seek, R2: Read + Seek>(
+    old: &mut BufReader<R1>,
+    new: &mut BufReader<R2>,
     old_size: usize,
     new_size: usize,
 ) -> Result<Option<BinaryDiffChunk>> {
@@ -0,0 +1,248 @@
+use super::merge_chunks;
+use crate::binary_diff::binary_diff_chunk::BinaryDiffChunk;
+use crate::binary_diff::error::BinaryDiffError;
+use crate::binary_diff::result::Result;
+use std::cmp::{max, min};
+use std::collections::HashMap;
+use std::io::Read;
+
+// Common bytes shorter than this value are not regarded as synchronization point after changes
+const SYNC_LENGTH: usize = 4;
+// Synchronization point is searched in this window first, and the window grows up to lookahead
+const MIN_SYNC_WINDOW: usize = 0x40;
+
+// Reader that keeps bytes not consumed yet
+struct StreamBuffer<R: Read> {
+    reader: R,
+    bytes: Vec<u8>,
+    start: usize,
+    // Offset of the first byte not consumed yet
+    position: usize,
+    eof: bool,
+}
+
+impl<R: Read> StreamBuffer<R> {
+    fn new(reader: R) -> Self {
+        Self {
+            reader,
+            bytes: vec![],
+            start: 0,
+            position: 0,
+            eof: false,
+        }
+    }
+
+    // Reads bytes until `length` bytes are available or reaches the end of stream
+    fn fill(&mut self, length: usize) -> Result<()> {
+        // NOTE: Consumed bytes are dropped only when they are more than half of the buffer,
+        // so that each byte is moved constant times on average
+        if self.start > self.bytes.len() / 2 {
+            self.bytes.drain(..self.start);
+            self.start = 0;
+        }
+        let available = self.bytes.len() - self.start;
+        if self.eof || available >= length {
+            return Ok(());
+        }
+        let required = length - available;
+        let read = self
+            .reader
+            .by_ref()
+            .take(required as u64)
+            .read_to_end(&mut self.bytes)
+            .map_err(BinaryDiffError::IoError)?;
+        self.eof = read < required;
+        Ok(())
+    }
+
+    fn available(&self) -> &[u8] {
+        &self.bytes[self.start..]
+    }
+
+    fn consume(&mut self, length: usize) {
+        self.start += length;
+        self.position += length;
+    }
+}
+
+fn common_prefix_length(a: &[u8], b: &[u8]) -> usize {
+    a.iter().zip(b.iter()).take_while(|(a, b)| a == b).count()
+}
+
+fn common_suffix_length(a: &[u8], b: &[u8]) -> usize {
+    a.iter()
+        .rev()
+        .zip(b.iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count()
+}
+
+// Finds (i, j) that old[i..] and new[j..] start with the same SYNC_LENGTH bytes in `window`.
+// Minimizes i + j, i.e. total length of Delete() and Insert() chunks.
+fn find_sync_point(old: &[u8], new: &[u8], window: usize) -> Option<(usize, usize)> {
+    let (old, new) = (
+        &old[..min(old.len(), window)],
+        &new[..min(new.len(), window)],
+    );
+    if old.len() < SYNC_LENGTH || new.len() < SYNC_LENGTH {
+        return None;
+    }
+
+    let mut positions = HashMap::new();
+    for (j, bytes) in new.windows(SYNC_LENGTH).enumerate() {
+        positions.entry(bytes).or_insert(j);
+    }
+    let mut sync_point: Option<(usize, usize)> = None;
+    for (i, bytes) in old.windows(SYNC_LENGTH).enumerate() {
+        if let Some((best_i, best_j)) = sync_point {
+            if i >= best_i + best_j {
+                break;
+            }
+        }
+        if let Some(j) = positions.get(bytes) {
+            if sync_point.map_or(true, |(best_i, best_j)| i + j < best_i + best_j) {
+                sync_point = Some((i, *j));
+            }
+        }
+    }
+    sync_point
+}
+
+// get_streaming_chunks() should satisfy following requirements:
+//   - Reads `old` and `new` only once from the start, and never seeks them
+//   - Holds at most twice of `lookahead` bytes of each of `old` and `new` on memory
+//   - Changes longer than `lookahead` become one Delete() chunk followed by Insert() chunk per `lookahead` bytes
+// NOTE: `lookahead` smaller than MIN_SYNC_WINDOW is raised to MIN_SYNC_WINDOW
+pub fn get_streaming_chunks<R1: Read, R2: Read>(
+    old: R1,
+    new: R2,
+    lookahead: usize,
+) -> Result<Vec<BinaryDiffChunk>> {
+    let lookahead = max(lookahead, MIN_SYNC_WINDOW);
+    let mut old = StreamBuffer::new(old);
+    let mut new = StreamBuffer::new(new);
+    let mut chunks = vec![];
+
+    loop {
+        old.fill(lookahead)?;
+        new.fill(lookahead)?;
+        let (old_bytes, new_bytes) = (old.available(), new.available());
+        let offset = old.position;
+
+        if old_bytes.is_empty() && new_bytes.is_empty() {
+            break;
+        }
+        let same = common_prefix_length(old_bytes, new_bytes);
+        if same > 0 {
+            chunks.push(BinaryDiffChunk::Same(offset, same));
+            old.consume(same);
+            new.consume(same);
+            continue;
+        }
+
+        let mut window = MIN_SYNC_WINDOW;
+        let (deleted, inserted) = loop {
+            if let Some(sync_point) = find_sync_point(old_bytes, new_bytes, window) {
+                break sync_point;
+            }
+            if window >= lookahead {
+                // Remaining bytes end with common bytes shorter than SYNC_LENGTH
+                let same = if old.eof && new.eof {
+                    common_suffix_length(old_bytes, new_bytes)
+                } else {
+                    0
+                };
+                break (old_bytes.len() - same, new_bytes.len() - same);
+            }
+            window = min(window * 4, lookahead);
+        };
+        log::trace!(
+            "Synchronized: offset, deleted, inserted = {:#x}, {:#x}, {:#x}",
+            offset,
+            deleted,
+            inserted
+        );
+        chunks.push(BinaryDiffChunk::Delete(offset, deleted));
+        chunks.push(BinaryDiffChunk::Insert(
+            offset + deleted,
+            new_bytes[..inserted].to_vec(),
+        ));
+        old.consume(deleted);
+        new.consume(inserted);
+    }
+
+    Ok(merge_chunks(&chunks))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::get_streaming_chunks;
+    use crate::binary_diff::algorithm::apply_chunks;
+    use crate::binary_diff::binary_diff_chunk::BinaryDiffChunk;
+    use crate::binary_diff::binary_diff_chunk::BinaryDiffChunk::{Delete, Insert, Same};
+    use crate::test_helper::random_bytes;
+    use std::io::{BufReader, Cursor};
+
+    fn diff(old: &[u8], new: &[u8], lookahead: usize) -> Vec<BinaryDiffChunk> {
+        // NOTE: &[u8] implements Read but does not implement Seek
+        let chunks = get_streaming_chunks(old, new, lookahead).unwrap();
+
+        let mut patched = vec![];
+        apply_chunks(&chunks, &mut BufReader::new(Cursor::new(old)), &mut patched).unwrap();
+        assert_eq!(patched, new);
+        chunks
+    }
+
+    #[test]
+    fn test_get_streaming_chunks() {
+        assert_eq!(diff(&[], &[], 0x100), vec![]);
+        assert_eq!(diff(&[0, 1], &[], 0x100), vec![Delete(0, 2)]);
+        assert_eq!(diff(&[], &[0, 1], 0x100), vec![Insert(0, vec![0, 1])]);
+        assert_eq!(
+            diff(&[0, 1, 2, 3], &[0, 1, 9, 3], 0x100),
+            vec![Same(0, 2), Delete(2, 1), Insert(3, vec![9]), Same(3, 1)]
+        );
+
+        let mut seed = 0x1234_5678;
+        let old = random_bytes(&mut seed, 0x1000);
+        let inserted = random_bytes(&mut seed, 0x20);
+        let mut new = [&old[0..0x300], &inserted, &old[0x300..0x800], &old[0x810..]].concat();
+        new[0x123] = !new[0x123];
+        assert_eq!(
+            diff(&old, &new, 0x100),
+            vec![
+                Same(0, 0x123),
+                Delete(0x123, 1),
+                Insert(0x124, vec![!old[0x123]]),
+                Same(0x124, 0x1dc),
+                Insert(0x300, inserted),
+                Same(0x300, 0x500),
+                Delete(0x800, 0x10),
+                Same(0x810, 0x7f0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_get_streaming_chunks_beyond_lookahead() {
+        let mut seed = 0x1234_5678;
+        let old = random_bytes(&mut seed, 0x1000);
+        let inserted = random_bytes(&mut seed, 0x200);
+        let new = [&old[0..0x400], &inserted, &old[0x400..]].concat();
+
+        // Inserted bytes are too long to synchronize
+        assert_eq!(
+            diff(&old, &new, 0x100),
+            vec![
+                Same(0, 0x400),
+                Delete(0x400, 0xc00),
+                Insert(0x1000, new[0x400..].to_vec())
+            ]
+        );
+
+        assert_eq!(
+            diff(&old, &new, 0x400),
+            vec![Same(0, 0x400), Insert(0x400, inserted), Same(0x400, 0xc00)]
+        );
+    }
+}
@@ -0,0 +1,246 @@
+use super::merge_chunks;
+use crate::binary_diff::binary_diff_chunk::BinaryDiffChunk;
+use crate::binary_diff::helper::get_suffix_array;
+use std::cmp::max;
+
+// new[new_offset..new_offset+length] equals to old[old_offset..old_offset+length]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+struct Match {
+    old_offset: usize,
+    new_offset: usize,
+    length: usize,
+}
+
+fn common_prefix_length(a: &[u8], b: &[u8]) -> usize {
+    a.iter().zip(b.iter()).take_while(|(a, b)| a == b).count()
+}
+
+// Finds the longest prefix of `bytes` that appears in `old`. Returns (offset in `old`, length)
+fn find_longest_match(old: &[u8], suffix_array: &[usize], bytes: &[u8]) -> (usize, usize) {
+    // Suffix that has the longest common prefix with `bytes` is adjacent to insertion point of `bytes`
+    let index = suffix_array.partition_point(|offset| &old[*offset..] < bytes);
+    [index.checked_sub(1), Some(index)]
+        .iter()
+        .flatten()
+        .filter_map(|index| suffix_array.get(*index))
+        .map(|offset| (*offset, common_prefix_length(&old[*offset..], bytes)))
+        .max_by_key(|(_, length)| *length)
+        .unwrap_or((0, 0))
+}
+
+// Scans `new` and picks the longest match at each position from anywhere of `old`
+fn find_matches(old: &[u8], new: &[u8], min_match_length: usize) -> Vec<Match> {
+    let suffix_array = get_suffix_array(old);
+    let mut matches = vec![];
+    let mut new_offset = 0;
+    while new_offset < new.len() {
+        let (old_offset, length) = find_longest_match(old, &suffix_array, &new[new_offset..]);
+        if length >= min_match_length {
+            matches.push(Match {
+                old_offset,
+                new_offset,
+                length,
+            });
+            new_offset += length;
+        } else {
+            new_offset += 1;
+        }
+    }
+    matches
+}
+
+// Selects matches in ascending order of offsets in both of `old` and `new` that maximize total length.
+// `matches` must be sorted by `new_offset` and must not overlap in `new`.
+// Returns whether each of `matches` is selected.
+fn select_matches(matches: &[Match]) -> Vec<bool> {
+    let mut ends: Vec<usize> = matches.iter().map(|m| m.old_offset + m.length).collect();
+    ends.sort_unstable();
+    ends.dedup();
+
+    // Fenwick tree of (total length, index of last match) to get prefix maximum over `ends`
+    let mut tree: Vec<(usize, Option<usize>)> = vec![(0, None); ends.len() + 1];
+    // (total length of matches ending with this match, index of previous match)
+    let mut chains: Vec<(usize, Option<usize>)> = Vec::with_capacity(matches.len());
+
+    for (index, m) in matches.iter().enumerate() {
+        // Best chain among matches that end before this match starts
+        let mut i = ends.partition_point(|end| *end <= m.old_offset);
+        let mut best = (0, None);
+        while i > 0 {
+            if tree[i].0 > best.0 {
+                best = tree[i];
+            }
+            i -= i & i.wrapping_neg();
+        }
+        let chain = (best.0 + m.length, Some(index));
+        chains.push((chain.0, best.1));
+
+        let mut i = ends.partition_point(|end| *end < m.old_offset + m.length) + 1;
+        while i < tree.len() {
+            if tree[i].0 < chain.0 {
+                tree[i] = chain;
+            }
+            i += i & i.wrapping_neg();
+        }
+    }
+
+    let mut selected = vec![false; matches.len()];
+    let mut last = chains
+        .iter()
+        .enumerate()
+        .max_by_key(|(_, (total, _))| *total)
+        .map(|(index, _)| index);
+    while let Some(index) = last {
+        selected[index] = true;
+        last = chains[index].1;
+    }
+    selected
+}
+
+// get_suffix_array_chunks() should satisfy following requirements:
+//   - Finds matches of at least `min_match_length` bytes from anywhere of `old` (like bsdiff)
+//   - Maximizes total length of Same() chunks made from the matches
+//   - Other matches (i.e. moved or duplicated blocks) become Copy() chunks
+//   - Changes between two Same() chunks are merged into one Delete() chunk followed by Insert() and Copy() chunks
+pub fn get_suffix_array_chunks(
+    old: &[u8],
+    new: &[u8],
+    min_match_length: usize,
+) -> Vec<BinaryDiffChunk> {
+    let matches = find_matches(old, new, max(min_match_length, 1));
+    log::trace!("matches = {:?}", matches);
+
+    let mut chunks = vec![];
+    let (mut old_position, mut new_position) = (0, 0);
+    // NOTE: merge_chunks() moves Delete() chunks before Insert() and Copy() chunks
+    for (m, selected) in matches.iter().zip(select_matches(&matches)) {
+        chunks.push(BinaryDiffChunk::Insert(
+            old_position,
+            new[new_position..m.new_offset].to_vec(),
+        ));
+        if !selected {
+            chunks.push(BinaryDiffChunk::Copy(old_position, m.old_offset, m.length));
+            new_position = m.new_offset + m.length;
+            continue;
+        }
+        chunks.push(BinaryDiffChunk::Delete(
+            old_position,
+            m.old_offset - old_position,
+        ));
+        chunks.push(BinaryDiffChunk::Same(m.old_offset, m.length));
+        old_position = m.old_offset + m.length;
+        new_position = m.new_offset + m.length;
+    }
+    chunks.push(BinaryDiffChunk::Insert(
+        old_position,
+        new[new_position..].to_vec(),
+    ));
+    chunks.push(BinaryDiffChunk::Delete(
+        old_position,
+        old.len() - old_position,
+    ));
+
+    merge_chunks(&chunks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find_matches, get_suffix_array_chunks, select_matches, Match};
+    use crate::binary_diff::algorithm::apply_chunks;
+    use crate::binary_diff::binary_diff_chunk::BinaryDiffChunk;
+    use crate::binary_diff::binary_diff_chunk::BinaryDiffChunk::{Copy, Delete, Insert, Same};
+    use crate::test_helper::random_bytes;
+    use std::io::{BufReader, Cursor};
+
+    fn apply(chunks: &[BinaryDiffChunk], old: &[u8]) -> Vec<u8> {
+        let mut patched = vec![];
+        apply_chunks(chunks, &mut BufReader::new(Cursor::new(old)), &mut patched).unwrap();
+        patched
+    }
+
+    #[test]
+    fn test_get_suffix_array_chunks() {
+        assert_eq!(get_suffix_array_chunks(&[], &[], 8), vec![]);
+        assert_eq!(get_suffix_array_chunks(&[0, 1], &[], 8), vec![Delete(0, 2)]);
+        assert_eq!(
+            get_suffix_array_chunks(&[], &[0, 1], 8),
+            vec![Insert(0, vec![0, 1])]
+        );
+
+        let old = b"0123456789abcdef".to_vec();
+        let new = b"0123456789xyzabcdef".to_vec();
+        assert_eq!(
+            get_suffix_array_chunks(&old, &new, 4),
+            vec![Same(0, 10), Insert(10, b"xyz".to_vec()), Same(10, 6)]
+        );
+    }
+
+    #[test]
+    fn test_get_suffix_array_chunks_far_insertion() {
+        // Inserted bytes are longer than windows of greedy engine
+        let mut seed = 0x1234_5678;
+        let old = random_bytes(&mut seed, 1000);
+        let inserted = random_bytes(&mut seed, 200);
+        let new = [&old[0..300], &inserted, &old[300..]].concat();
+
+        let chunks = get_suffix_array_chunks(&old, &new, 8);
+        assert_eq!(
+            chunks,
+            vec![Same(0, 300), Insert(300, inserted), Same(300, 700)]
+        );
+        assert_eq!(apply(&chunks, &old), new);
+    }
+
+    #[test]
+    fn test_get_suffix_array_chunks_relocation() {
+        let mut seed = 0x1234_5678;
+        let blocks: Vec<Vec<u8>> = (0..4).map(|_| random_bytes(&mut seed, 500)).collect();
+        let old = blocks.concat();
+        // Moves the first block to the end
+        let new = [&blocks[1], &blocks[2], &blocks[3][0..100], &blocks[0]].concat();
+
+        let chunks = get_suffix_array_chunks(&old, &new, 8);
+        assert_eq!(
+            chunks,
+            vec![
+                Delete(0, 500),
+                Same(500, 1100),
+                Delete(1600, 400),
+                Copy(2000, 0, 500)
+            ]
+        );
+        assert_eq!(apply(&chunks, &old), new);
+    }
+
+    #[test]
+    fn test_find_matches_relocation() {
+        let mut seed = 0x1234_5678;
+        let blocks: Vec<Vec<u8>> = (0..4).map(|_| random_bytes(&mut seed, 500)).collect();
+        let old = blocks.concat();
+        let new = [&blocks[1], &blocks[2], &blocks[3][0..100], &blocks[0]].concat();
+
+        // The first block moved to the end is found though it is not selected as Same() chunk
+        let matches = find_matches(&old, &new, 8);
+        let moved = Match {
+            old_offset: 0,
+            new_offset: 1100,
+            length: 500,
+        };
+        let index = matches.iter().position(|m| *m == moved).unwrap();
+        assert!(!select_matches(&matches)[index]);
+    }
+
+    #[test]
+    fn test_get_suffix_array_chunks_duplication() {
+        let mut seed = 0x1234_5678;
+        let old = random_bytes(&mut seed, 1000);
+        let new = [&old[0..600], &old[100..300], &old[600..]].concat();
+
+        let chunks = get_suffix_array_chunks(&old, &new, 8);
+        assert_eq!(
+            chunks,
+            vec![Same(0, 600), Copy(600, 100, 200), Same(600, 400)]
+        );
+        assert_eq!(apply(&chunks, &old), new);
+    }
+}
@@ -6,7 +6,7 @@ enum LoopFlag {
     Skip,
 }
 
-pub fn introduce_replace_chunk(original_chunks: &Vec<BinaryDiffChunk>) -> Vec<BinaryDiffChunk> {
+pub fn introduce_replace_chunk(original_chunks: &[BinaryDiffChunk]) -> Vec<BinaryDiffChunk> {
     let mut enhanced_chunks = vec![];
     let mut loop_flag = LoopFlag::Default; // TODO: Dirty. Do refactoring to use Reader<R: Read + Seek>
     for (i, chunk) in original_chunks.iter().enumerate() {
@@ -18,11 +18,7 @@ pub fn introduce_replace_chunk(original_chunks: &Vec<BinaryDiffChunk>) -> Vec<Bi
         if i < original_chunks.len() - 1 {
             if let BinaryDiffChunk::Delete(offset, length) = &chunk {
                 if let BinaryDiffChunk::Insert(_, bytes) = &original_chunks[i + 1] {
-                    enhanced_chunks.push(BinaryDiffChunk::Replace(
-                        offset.clone(),
-                        length.clone(),
-                        bytes.clone(),
-                    ));
+                    enhanced_chunks.push(BinaryDiffChunk::Replace(*offset, *length, bytes.clone()));
                     loop_flag = LoopFlag::Skip;
                     continue;
                 }
@@ -0,0 +1,157 @@
+use crate::binary_diff::binary_diff_chunk::BinaryDiffChunk;
+use crate::binary_diff::error::BinaryDiffError;
+use crate::binary_diff::result::Result;
+
+// invert_chunks() should satisfy following requirements:
+//   - Offsets of inverted chunks are in offset of patched binary
+//   - Delete() chunk comes before Insert() chunk at the same position, as BinaryDiff::new() does
+//   - Chunks are validated against `old` in the same manner as apply_chunks()
+pub fn invert_chunks(chunks: &[BinaryDiffChunk], old: &[u8]) -> Result<Vec<BinaryDiffChunk>> {
+    let mut inverted_chunks: Vec<BinaryDiffChunk> = vec![];
+    let mut original_position = 0usize;
+    let mut patched_position = 0usize;
+
+    for chunk in chunks.iter() {
+        if *chunk.offset() != original_position {
+            return Err(BinaryDiffError::ChunkOffsetMismatchError(
+                original_position,
+                chunk.clone(),
+            ));
+        }
+        let end = original_position + chunk.original_length();
+        let removed_bytes = match old.get(original_position..end) {
+            Some(bytes) => bytes,
+            None => {
+                return Err(BinaryDiffError::ChunkOutOfRangeError(
+                    old.len(),
+                    chunk.clone(),
+                ))
+            }
+        };
+
+        match chunk {
+            BinaryDiffChunk::Same(_, length) => {
+                inverted_chunks.push(BinaryDiffChunk::Same(patched_position, *length))
+            }
+            BinaryDiffChunk::Delete(_, _) => inverted_chunks.push(BinaryDiffChunk::Insert(
+                patched_position,
+                removed_bytes.to_vec(),
+            )),
+            BinaryDiffChunk::Replace(_, _, bytes) => inverted_chunks.push(
+                BinaryDiffChunk::Replace(patched_position, bytes.len(), removed_bytes.to_vec()),
+            ),
+            BinaryDiffChunk::Insert(_, _) | BinaryDiffChunk::Copy(_, _, _) => {
+                if let BinaryDiffChunk::Copy(_, source, length) = chunk {
+                    if *source > old.len() || old.len() - source < *length {
+                        return Err(BinaryDiffError::ChunkOutOfRangeError(
+                            old.len(),
+                            chunk.clone(),
+                        ));
+                    }
+                }
+
+                // Move preceding Insert() chunks (inverted from Delete()) after this Delete() chunk
+                let mut inserted_chunks = vec![];
+                while let Some(BinaryDiffChunk::Insert(offset, _)) = inverted_chunks.last() {
+                    if *offset != patched_position {
+                        break;
+                    }
+                    if let Some(BinaryDiffChunk::Insert(_, inserted_bytes)) = inverted_chunks.pop()
+                    {
+                        inserted_chunks.insert(0, inserted_bytes);
+                    }
+                }
+                inverted_chunks.push(BinaryDiffChunk::Delete(patched_position, chunk.length()));
+                for inserted_bytes in inserted_chunks {
+                    inverted_chunks.push(BinaryDiffChunk::Insert(
+                        patched_position + chunk.length(),
+                        inserted_bytes,
+                    ));
+                }
+            }
+        }
+
+        original_position += chunk.original_length();
+        patched_position += chunk.patched_length();
+    }
+
+    if original_position != old.len() {
+        return Err(BinaryDiffError::IncompleteChunksError(
+            original_position,
+            old.len(),
+        ));
+    }
+    Ok(inverted_chunks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::invert_chunks;
+    use crate::binary_diff::binary_diff_chunk::BinaryDiffChunk::{
+        Copy, Delete, Insert, Replace, Same,
+    };
+
+    #[test]
+    fn test_invert_chunks() {
+        let old = vec![0, 1, 2, 3, 4, 5];
+        let chunks = vec![
+            Same(0, 1),
+            Delete(1, 2),
+            Insert(3, vec![0xff]),
+            Same(3, 1),
+            Replace(4, 2, vec![0xaa, 0xbb, 0xcc]),
+        ];
+        let inverted = invert_chunks(&chunks, &old).unwrap();
+        assert_eq!(
+            inverted,
+            vec![
+                Same(0, 1),
+                Delete(1, 1),
+                Insert(2, vec![1, 2]),
+                Same(2, 1),
+                Replace(3, 3, vec![4, 5]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invert_chunks_successive_delete() {
+        let old = vec![0, 1, 2, 3];
+        let chunks = vec![
+            Delete(0, 1),
+            Delete(1, 2),
+            Insert(3, vec![0xff]),
+            Same(3, 1),
+        ];
+        let inverted = invert_chunks(&chunks, &old).unwrap();
+        assert_eq!(
+            inverted,
+            vec![
+                Delete(0, 1),
+                Insert(1, vec![0]),
+                Insert(1, vec![1, 2]),
+                Same(1, 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invert_chunks_copy() {
+        let old = vec![0, 1, 2, 3];
+        let chunks = vec![Same(0, 2), Delete(2, 1), Copy(3, 0, 2), Same(3, 1)];
+        let inverted = invert_chunks(&chunks, &old).unwrap();
+        assert_eq!(
+            inverted,
+            vec![Same(0, 2), Delete(2, 2), Insert(4, vec![2]), Same(4, 1)]
+        );
+
+        assert!(invert_chunks(&[Copy(0, 3, 2), Same(0, 4)], &old).is_err());
+    }
+
+    #[test]
+    fn test_invert_chunks_out_of_range() {
+        let old = vec![0, 1];
+        let chunks = vec![Same(0, 1), Delete(1, 2)];
+        assert!(invert_chunks(&chunks, &old).is_err());
+    }
+}
@@ -0,0 +1,291 @@
+use super::merge_chunks;
+use crate::binary_diff::binary_diff_chunk::BinaryDiffChunk;
+use crate::binary_diff::conflict_resolution::ConflictResolution;
+use crate::binary_diff::error::BinaryDiffError;
+use crate::binary_diff::result::Result;
+use std::ops::Range;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum Side {
+    Ours,
+    Theirs,
+}
+
+// Replaces base[start..end] with `bytes`
+#[derive(Debug, Eq, PartialEq)]
+struct Edit {
+    side: Side,
+    start: usize,
+    end: usize,
+    bytes: Vec<u8>,
+}
+
+impl Edit {
+    fn is_insertion(&self) -> bool {
+        self.start == self.end
+    }
+
+    fn is_same_change(&self, other: &Edit) -> bool {
+        (self.start, self.end, &self.bytes) == (other.start, other.end, &other.bytes)
+    }
+
+    fn conflicts_with(&self, other: &Edit) -> bool {
+        if self.side == other.side || self.is_same_change(other) {
+            return false;
+        }
+        match (self.is_insertion(), other.is_insertion()) {
+            // Order of inserted bytes is ambiguous
+            (true, true) => self.start == other.start,
+            // Inserted into deleted range
+            (true, false) => other.start < self.start && self.start < other.end,
+            (false, true) => self.start < other.start && other.start < self.end,
+            (false, false) => self.start < other.end && other.start < self.end,
+        }
+    }
+}
+
+fn to_edits(chunks: &[BinaryDiffChunk], side: Side, base: &[u8]) -> Result<Vec<Edit>> {
+    let base_size = base.len();
+    let mut position = 0usize;
+    let mut materialized_chunks = vec![];
+    for chunk in chunks.iter() {
+        if *chunk.offset() != position {
+            return Err(BinaryDiffError::ChunkOffsetMismatchError(
+                position,
+                chunk.clone(),
+            ));
+        }
+        if base_size - position < chunk.original_length() {
+            return Err(BinaryDiffError::ChunkOutOfRangeError(
+                base_size,
+                chunk.clone(),
+            ));
+        }
+        position += chunk.original_length();
+
+        // Copied bytes are compared as inserted bytes
+        materialized_chunks.push(match chunk {
+            BinaryDiffChunk::Copy(offset, source, length) => {
+                match base.get(*source..).and_then(|bytes| bytes.get(..*length)) {
+                    Some(bytes) => BinaryDiffChunk::Insert(*offset, bytes.to_vec()),
+                    None => {
+                        return Err(BinaryDiffError::ChunkOutOfRangeError(
+                            base_size,
+                            chunk.clone(),
+                        ))
+                    }
+                }
+            }
+            _ => chunk.clone(),
+        });
+    }
+    if position != base_size {
+        return Err(BinaryDiffError::IncompleteChunksError(position, base_size));
+    }
+
+    let mut edits: Vec<Edit> = vec![];
+    for chunk in merge_chunks(&materialized_chunks).into_iter() {
+        match chunk {
+            BinaryDiffChunk::Delete(offset, length) => edits.push(Edit {
+                side,
+                start: offset,
+                end: offset + length,
+                bytes: vec![],
+            }),
+            BinaryDiffChunk::Insert(offset, bytes) => match edits.last_mut() {
+                // merge_chunks() puts Insert() chunk right after Delete() chunk
+                Some(edit) if edit.end == offset && edit.bytes.is_empty() => edit.bytes = bytes,
+                _ => edits.push(Edit {
+                    side,
+                    start: offset,
+                    end: offset,
+                    bytes,
+                }),
+            },
+            _ => (),
+        }
+    }
+    Ok(edits)
+}
+
+fn push_edit(chunks: &mut Vec<BinaryDiffChunk>, position: &mut usize, edit: Edit) {
+    if *position < edit.start {
+        chunks.push(BinaryDiffChunk::Same(*position, edit.start - *position));
+    }
+    chunks.push(BinaryDiffChunk::Delete(edit.start, edit.end - edit.start));
+    chunks.push(BinaryDiffChunk::Insert(edit.end, edit.bytes));
+    *position = edit.end;
+}
+
+// merge3_chunks() should satisfy following requirements:
+//   - Changes made by only one of `ours` and `theirs` are taken as is
+//   - Identical changes made by both of them are taken once
+//   - Returns list of conflicting ranges in offset of `base` with merged chunks
+// NOTE: Returned chunks are not merged. Apply merge_chunks() to normalize them.
+// NOTE: Copy() chunks are turned into Insert() chunks of copied bytes.
+pub fn merge3_chunks(
+    base: &[u8],
+    ours: &[BinaryDiffChunk],
+    theirs: &[BinaryDiffChunk],
+    resolution: ConflictResolution,
+) -> Result<(Vec<BinaryDiffChunk>, Vec<Range<usize>>)> {
+    let base_size = base.len();
+    let mut edits = to_edits(ours, Side::Ours, base)?;
+    let our_edits_count = edits.len();
+    for edit in to_edits(theirs, Side::Theirs, base)?.into_iter() {
+        if !edits[0..our_edits_count]
+            .iter()
+            .any(|our_edit| our_edit.is_same_change(&edit))
+        {
+            edits.push(edit);
+        }
+    }
+    edits.sort_by_key(|edit| (edit.start, edit.end));
+
+    // Group mutually conflicting edits
+    let mut clusters: Vec<Vec<Edit>> = vec![];
+    for edit in edits.into_iter() {
+        match clusters.last_mut() {
+            Some(cluster) if cluster.iter().any(|member| member.conflicts_with(&edit)) => {
+                cluster.push(edit)
+            }
+            _ => clusters.push(vec![edit]),
+        }
+    }
+
+    let mut chunks = vec![];
+    let mut conflicts = vec![];
+    let mut position = 0usize;
+    for cluster in clusters.into_iter() {
+        if cluster.len() == 1 {
+            for edit in cluster.into_iter() {
+                push_edit(&mut chunks, &mut position, edit);
+            }
+            continue;
+        }
+
+        let start = cluster.iter().map(|edit| edit.start).min().unwrap_or(0);
+        let end = cluster.iter().map(|edit| edit.end).max().unwrap_or(0);
+        log::trace!("[*] conflict = {:?}", cluster);
+        conflicts.push(start..end);
+
+        let preferred_side = match resolution {
+            ConflictResolution::PreferOurs => Side::Ours,
+            ConflictResolution::PreferTheirs => Side::Theirs,
+            ConflictResolution::Fail => continue,
+        };
+        for edit in cluster.into_iter() {
+            if edit.side == preferred_side {
+                push_edit(&mut chunks, &mut position, edit);
+            }
+        }
+    }
+    if position < base_size {
+        chunks.push(BinaryDiffChunk::Same(position, base_size - position));
+    }
+
+    if resolution == ConflictResolution::Fail && !conflicts.is_empty() {
+        return Err(BinaryDiffError::ConflictError(conflicts));
+    }
+    Ok((chunks, conflicts))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::merge3_chunks;
+    use crate::binary_diff::algorithm::merge_chunks;
+    use crate::binary_diff::binary_diff_chunk::BinaryDiffChunk::{
+        Copy, Delete, Insert, Replace, Same,
+    };
+    use crate::binary_diff::conflict_resolution::ConflictResolution;
+    use crate::binary_diff::error::BinaryDiffError;
+
+    #[test]
+    fn test_merge3_chunks_without_conflict() {
+        let base = [0, 1, 2, 3, 4, 5];
+        let ours = vec![Same(0, 1), Replace(1, 1, vec![0xaa]), Same(2, 4)];
+        let theirs = vec![Same(0, 4), Delete(4, 1), Same(5, 1), Insert(6, vec![0xbb])];
+        let (chunks, conflicts) =
+            merge3_chunks(&base, &ours, &theirs, ConflictResolution::Fail).unwrap();
+        assert!(conflicts.is_empty());
+        assert_eq!(
+            merge_chunks(&chunks),
+            vec![
+                Same(0, 1),
+                Delete(1, 1),
+                Insert(2, vec![0xaa]),
+                Same(2, 2),
+                Delete(4, 1),
+                Same(5, 1),
+                Insert(6, vec![0xbb])
+            ]
+        );
+    }
+
+    #[test]
+    fn test_merge3_chunks_same_change() {
+        let ours = vec![Same(0, 1), Replace(1, 1, vec![0xaa]), Same(2, 2)];
+        let (chunks, conflicts) =
+            merge3_chunks(&[0, 1, 2, 3], &ours, &ours, ConflictResolution::Fail).unwrap();
+        assert!(conflicts.is_empty());
+        assert_eq!(merge_chunks(&chunks), merge_chunks(&ours));
+    }
+
+    #[test]
+    fn test_merge3_chunks_with_conflict() {
+        let base = [0, 1, 2, 3, 4, 5];
+        let ours = vec![Same(0, 1), Replace(1, 2, vec![0xaa]), Same(3, 3)];
+        let theirs = vec![Same(0, 2), Replace(2, 2, vec![0xbb]), Same(4, 2)];
+
+        let (chunks, conflicts) =
+            merge3_chunks(&base, &ours, &theirs, ConflictResolution::PreferOurs).unwrap();
+        assert_eq!(conflicts, vec![1..4]);
+        assert_eq!(merge_chunks(&chunks), merge_chunks(&ours));
+
+        let (chunks, conflicts) =
+            merge3_chunks(&base, &ours, &theirs, ConflictResolution::PreferTheirs).unwrap();
+        assert_eq!(conflicts, vec![1..4]);
+        assert_eq!(merge_chunks(&chunks), merge_chunks(&theirs));
+
+        match merge3_chunks(
+            &[0, 1, 2, 3, 4, 5],
+            &ours,
+            &theirs,
+            ConflictResolution::Fail,
+        ) {
+            Err(BinaryDiffError::ConflictError(conflicts)) => assert_eq!(conflicts, vec![1..4]),
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_merge3_chunks_insertion_conflict() {
+        let ours = vec![Same(0, 1), Insert(1, vec![0xaa]), Same(1, 1)];
+        let theirs = vec![Same(0, 1), Insert(1, vec![0xbb]), Same(1, 1)];
+        let (_, conflicts) =
+            merge3_chunks(&[0, 1], &ours, &theirs, ConflictResolution::PreferOurs).unwrap();
+        assert_eq!(conflicts, vec![1..1]);
+    }
+
+    #[test]
+    fn test_merge3_chunks_copy() {
+        let base = [0, 1, 2, 3];
+        let ours = vec![Same(0, 4), Copy(4, 0, 2)];
+        let theirs = vec![Same(0, 4), Insert(4, vec![0, 1])];
+        let (chunks, conflicts) =
+            merge3_chunks(&base, &ours, &theirs, ConflictResolution::Fail).unwrap();
+        assert!(conflicts.is_empty());
+        assert_eq!(
+            merge_chunks(&chunks),
+            vec![Same(0, 4), Insert(4, vec![0, 1])]
+        );
+
+        assert!(merge3_chunks(
+            &base,
+            &[Copy(0, 3, 2), Same(0, 4)],
+            &theirs,
+            ConflictResolution::Fail
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,170 @@
+use crate::binary_diff::binary_diff_chunk::BinaryDiffChunk;
+
+// merge_chunks() should satisfy following requirements:
+//   - Adjacent Same() chunks are merged into one
+//   - Changes between two Same() chunks are merged into one Delete() chunk followed by Insert() and Copy() chunks
+//   - Adjacent Insert() chunks and Copy() chunks of contiguous source are merged into one
+//   - Chunks of no length are dropped
+// NOTE: Replace() chunks are split into Delete() and Insert(). Apply introduce_replace_chunk() to restore them.
+pub fn merge_chunks(chunks: &[BinaryDiffChunk]) -> Vec<BinaryDiffChunk> {
+    let mut merged_chunks = vec![];
+    // Pending changes: (offset, deleted length, inserted Insert() and Copy() chunks)
+    let mut changes: Option<(usize, usize, Vec<BinaryDiffChunk>)> = None;
+
+    for chunk in chunks.iter() {
+        match chunk {
+            BinaryDiffChunk::Same(offset, length) => {
+                if *length == 0 {
+                    continue;
+                }
+                if let Some(changes) = changes.take() {
+                    push_changes(&mut merged_chunks, changes);
+                }
+                match merged_chunks.last_mut() {
+                    Some(BinaryDiffChunk::Same(last_offset, last_length))
+                        if *last_offset + *last_length == *offset =>
+                    {
+                        *last_length += length
+                    }
+                    _ => merged_chunks.push(chunk.clone()),
+                }
+            }
+            BinaryDiffChunk::Insert(offset, _) | BinaryDiffChunk::Copy(offset, _, _) => {
+                let changes = changes.get_or_insert((*offset, 0, vec![]));
+                push_inserted(&mut changes.2, chunk.clone());
+            }
+            BinaryDiffChunk::Delete(offset, length) => {
+                let changes = changes.get_or_insert((*offset, 0, vec![]));
+                changes.1 += length;
+            }
+            BinaryDiffChunk::Replace(offset, length, bytes) => {
+                let changes = changes.get_or_insert((*offset, 0, vec![]));
+                changes.1 += length;
+                push_inserted(
+                    &mut changes.2,
+                    BinaryDiffChunk::Insert(*offset, bytes.clone()),
+                );
+            }
+        }
+    }
+    if let Some(changes) = changes.take() {
+        push_changes(&mut merged_chunks, changes);
+    }
+
+    merged_chunks
+}
+
+fn push_inserted(inserted: &mut Vec<BinaryDiffChunk>, chunk: BinaryDiffChunk) {
+    if chunk.length() == 0 {
+        return;
+    }
+    match (inserted.last_mut(), chunk) {
+        (Some(BinaryDiffChunk::Insert(_, last_bytes)), BinaryDiffChunk::Insert(_, bytes)) => {
+            last_bytes.extend_from_slice(&bytes)
+        }
+        (
+            Some(BinaryDiffChunk::Copy(_, last_source, last_length)),
+            BinaryDiffChunk::Copy(_, source, length),
+        ) if *last_source + *last_length == source => *last_length += length,
+        (_, chunk) => inserted.push(chunk),
+    }
+}
+
+fn push_changes(chunks: &mut Vec<BinaryDiffChunk>, changes: (usize, usize, Vec<BinaryDiffChunk>)) {
+    let (offset, length, inserted) = changes;
+    if length > 0 {
+        chunks.push(BinaryDiffChunk::Delete(offset, length));
+    }
+    for chunk in inserted.into_iter() {
+        chunks.push(match chunk {
+            BinaryDiffChunk::Copy(_, source, copied_length) => {
+                BinaryDiffChunk::Copy(offset + length, source, copied_length)
+            }
+            BinaryDiffChunk::Insert(_, bytes) => BinaryDiffChunk::Insert(offset + length, bytes),
+            chunk => chunk,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::merge_chunks;
+    use crate::binary_diff::binary_diff_chunk::BinaryDiffChunk::{
+        Copy, Delete, Insert, Replace, Same,
+    };
+
+    #[test]
+    fn test_merge_chunks() {
+        let original = vec![
+            Same(0x0, 0x6),
+            Delete(0x6, 0x2),
+            Same(0x8, 0x2),
+            Delete(0xa, 0x1),
+            Insert(0xb, vec![0x18]),
+            Insert(0xb, vec![0x03, 0x18]),
+            Same(0xb, 21),
+        ];
+        assert_eq!(
+            merge_chunks(&original),
+            vec![
+                Same(0x0, 0x6),
+                Delete(0x6, 0x2),
+                Same(0x8, 0x2),
+                Delete(0xa, 0x1),
+                Insert(0xb, vec![0x18, 0x03, 0x18]),
+                Same(0xb, 21),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_merge_chunks_same() {
+        let original = vec![Same(0, 2), Same(2, 0), Same(2, 3), Delete(5, 1)];
+        assert_eq!(merge_chunks(&original), vec![Same(0, 5), Delete(5, 1)]);
+    }
+
+    #[test]
+    fn test_merge_chunks_delete_insert_delete() {
+        let original = vec![
+            Same(0, 1),
+            Delete(1, 2),
+            Insert(3, vec![0xff]),
+            Delete(3, 1),
+            Replace(4, 1, vec![0xfe]),
+            Same(5, 1),
+        ];
+        assert_eq!(
+            merge_chunks(&original),
+            vec![
+                Same(0, 1),
+                Delete(1, 4),
+                Insert(5, vec![0xff, 0xfe]),
+                Same(5, 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_merge_chunks_copy() {
+        let original = vec![
+            Same(0, 1),
+            Copy(1, 4, 2),
+            Copy(1, 6, 1),
+            Insert(1, vec![0xff]),
+            Delete(1, 2),
+            Copy(3, 0, 1),
+            Same(3, 4),
+        ];
+        assert_eq!(
+            merge_chunks(&original),
+            vec![
+                Same(0, 1),
+                Delete(1, 2),
+                Copy(3, 4, 3),
+                Insert(3, vec![0xff]),
+                Copy(3, 0, 1),
+                Same(3, 4)
+            ]
+        );
+    }
+}
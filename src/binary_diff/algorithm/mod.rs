@@ -1,9 +1,29 @@
+mod apply_chunks;
+mod compose_chunks;
 mod get_delete_chunk;
 mod get_insert_chunk;
+mod get_myers_chunks;
+mod get_rolling_hash_chunks;
 mod get_same_chunk;
+mod get_streaming_chunks;
+mod get_suffix_array_chunks;
 mod introduce_replace_chunk;
+mod invert_chunks;
+mod merge3_chunks;
+mod merge_chunks;
+mod validate_chunks;
 
+pub(super) use apply_chunks::apply_chunks;
+pub(super) use compose_chunks::compose_chunks;
 pub(super) use get_delete_chunk::get_delete_chunk;
 pub(super) use get_insert_chunk::get_insert_chunk;
+pub(super) use get_myers_chunks::get_myers_chunks;
+pub(super) use get_rolling_hash_chunks::get_rolling_hash_chunks;
 pub(super) use get_same_chunk::get_same_chunk;
+pub(super) use get_streaming_chunks::get_streaming_chunks;
+pub(super) use get_suffix_array_chunks::get_suffix_array_chunks;
 pub(super) use introduce_replace_chunk::introduce_replace_chunk;
+pub(super) use invert_chunks::invert_chunks;
+pub(super) use merge3_chunks::merge3_chunks;
+pub(super) use merge_chunks::merge_chunks;
+pub(super) use validate_chunks::validate_chunks;
@@ -0,0 +1,131 @@
+use crate::binary_diff::binary_diff_chunk::BinaryDiffChunk;
+use crate::binary_diff::error::BinaryDiffError;
+use crate::binary_diff::result::Result;
+
+// validate_chunks() checks that chunks satisfy following requirements:
+//   - Each chunk starts at the end of the previous one (in offset of original binary)
+//   - No chunk is of no length
+//   - No adjacent chunks can be merged into one, i.e. changes between two Same() chunks are
+//     one Delete() or Replace() chunk followed by Insert() and Copy() chunks of which
+//     adjacent ones are of different kinds or Copy() chunks of non-contiguous source
+// NOTE: Delete() followed by Insert() is accepted though introduce_replace_chunk() turns it into Replace()
+pub fn validate_chunks(chunks: &[BinaryDiffChunk]) -> Result<()> {
+    let mut position = 0usize;
+    let mut previous: Option<&BinaryDiffChunk> = None;
+    for chunk in chunks.iter() {
+        if *chunk.offset() != position {
+            return Err(BinaryDiffError::ChunkOffsetMismatchError(
+                position,
+                chunk.clone(),
+            ));
+        }
+        if is_empty(chunk) {
+            return Err(BinaryDiffError::EmptyChunkError(chunk.clone()));
+        }
+        if let Some(previous) = previous {
+            if is_mergeable(previous, chunk) {
+                return Err(BinaryDiffError::UnmergedChunksError(
+                    previous.clone(),
+                    chunk.clone(),
+                ));
+            }
+        }
+        position += chunk.original_length();
+        previous = Some(chunk);
+    }
+    Ok(())
+}
+
+fn is_empty(chunk: &BinaryDiffChunk) -> bool {
+    match chunk {
+        // Replace() chunk that deletes or inserts nothing should be Insert() or Delete()
+        BinaryDiffChunk::Replace(_, length, bytes) => *length == 0 || bytes.is_empty(),
+        _ => chunk.length() == 0,
+    }
+}
+
+fn is_mergeable(previous: &BinaryDiffChunk, chunk: &BinaryDiffChunk) -> bool {
+    match (previous, chunk) {
+        (BinaryDiffChunk::Same(_, _), BinaryDiffChunk::Same(_, _)) => true,
+        (BinaryDiffChunk::Same(_, _), _) => false,
+        (_, BinaryDiffChunk::Delete(_, _)) | (_, BinaryDiffChunk::Replace(_, _, _)) => true,
+        (BinaryDiffChunk::Insert(_, _), BinaryDiffChunk::Insert(_, _)) => true,
+        (
+            BinaryDiffChunk::Copy(_, last_source, last_length),
+            BinaryDiffChunk::Copy(_, source, _),
+        ) => *last_source + *last_length == *source,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::binary_diff::algorithm::validate_chunks;
+    use crate::binary_diff::binary_diff_chunk::BinaryDiffChunk::{
+        Copy, Delete, Insert, Replace, Same,
+    };
+    use crate::binary_diff::error::BinaryDiffError;
+
+    #[test]
+    fn test_validate_chunks() {
+        let chunks = vec![
+            Same(0, 1),
+            Delete(1, 2),
+            Insert(3, vec![0xff]),
+            Copy(3, 0, 1),
+            Same(3, 1),
+            Replace(4, 2, vec![0xaa]),
+            Copy(6, 4, 2),
+            Copy(6, 0, 1),
+        ];
+        let result = validate_chunks(&chunks);
+        assert!(result.is_ok(), "{:?}", result);
+        assert!(validate_chunks(&[]).is_ok());
+    }
+
+    #[test]
+    fn test_validate_chunks_offset_mismatch() {
+        match validate_chunks(&[Same(0, 1), Delete(2, 2)]) {
+            Err(BinaryDiffError::ChunkOffsetMismatchError(1, chunk)) => {
+                assert_eq!(chunk, Delete(2, 2))
+            }
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_validate_chunks_empty_chunk() {
+        match validate_chunks(&[Same(0, 1), Insert(1, vec![]), Same(1, 1)]) {
+            Err(BinaryDiffError::EmptyChunkError(chunk)) => assert_eq!(chunk, Insert(1, vec![])),
+            other => panic!("{:?}", other),
+        }
+        match validate_chunks(&[Replace(0, 0, vec![0xff])]) {
+            Err(BinaryDiffError::EmptyChunkError(chunk)) => {
+                assert_eq!(chunk, Replace(0, 0, vec![0xff]))
+            }
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_validate_chunks_unmerged() {
+        let unmerged = [
+            vec![Same(0, 1), Same(1, 1)],
+            vec![Delete(0, 1), Delete(1, 1)],
+            vec![Insert(0, vec![0]), Insert(0, vec![1])],
+            vec![Insert(0, vec![0]), Delete(0, 1)],
+            vec![Delete(0, 1), Insert(1, vec![0]), Delete(1, 1)],
+            vec![Replace(0, 1, vec![0]), Replace(1, 1, vec![1])],
+            vec![Copy(0, 0, 1), Copy(0, 1, 1)],
+        ];
+        for chunks in unmerged.iter() {
+            match validate_chunks(chunks) {
+                Err(BinaryDiffError::UnmergedChunksError(previous, chunk)) => {
+                    assert_eq!(previous, chunks[chunks.len() - 2]);
+                    assert_eq!(chunk, chunks[chunks.len() - 1]);
+                }
+                other => panic!("{:?}: {:?}", chunks, other),
+            }
+        }
+    }
+}
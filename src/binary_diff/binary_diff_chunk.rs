@@ -13,6 +13,8 @@ pub enum BinaryDiffChunk {
     Insert(Offset, Bytes),
     Delete(Offset, Length),
     Replace(Offset, Length, Bytes),
+    // (offset, source offset, length): Inserts bytes copied from `source offset` of original binary
+    Copy(Offset, Offset, Length),
 }
 
 impl BinaryDiffChunk {
@@ -22,16 +24,18 @@ impl BinaryDiffChunk {
             BinaryDiffChunk::Insert(offset, _) => offset,
             BinaryDiffChunk::Delete(offset, _) => offset,
             BinaryDiffChunk::Replace(offset, _, _) => offset,
+            BinaryDiffChunk::Copy(offset, _, _) => offset,
         }
     }
 
     // Returns how much bytes will be affected in original binary
     pub fn length(&self) -> Length {
         match self {
-            BinaryDiffChunk::Same(_, length) => length.clone(),
+            BinaryDiffChunk::Same(_, length) => *length,
             BinaryDiffChunk::Insert(_, bytes) => bytes.len(),
-            BinaryDiffChunk::Delete(_, length) => length.clone(),
-            BinaryDiffChunk::Replace(_, length, _) => length.clone(),
+            BinaryDiffChunk::Delete(_, length) => *length,
+            BinaryDiffChunk::Replace(_, length, _) => *length,
+            BinaryDiffChunk::Copy(_, _, length) => *length,
         }
     }
 
@@ -44,6 +48,14 @@ impl BinaryDiffChunk {
         }
     }
 
+    // Returns how much bytes will be consumed from original binary
+    pub fn original_length(&self) -> Length {
+        match self {
+            BinaryDiffChunk::Insert(_, _) | BinaryDiffChunk::Copy(_, _, _) => 0,
+            _ => self.length(),
+        }
+    }
+
     pub fn end(&self) -> Offset {
         self.offset() + self.length()
     }
@@ -54,13 +66,14 @@ impl BinaryDiffChunk {
             BinaryDiffChunk::Delete(_, _) => "Delete",
             BinaryDiffChunk::Insert(_, _) => "Insert",
             BinaryDiffChunk::Replace(_, _, _) => "Replace",
+            BinaryDiffChunk::Copy(_, _, _) => "Copy",
         }
     }
 }
 
 impl Ord for BinaryDiffChunk {
     fn cmp(&self, other: &Self) -> Ordering {
-        let res = self.offset().cmp(&other.offset());
+        let res = self.offset().cmp(other.offset());
         if res.is_eq() {
             match (self, other) {
                 (BinaryDiffChunk::Same(_, _), _) => Ordering::Greater,
@@ -114,6 +127,11 @@ impl fmt::Debug for BinaryDiffChunk {
                     stringify_bytes(bytes)
                 )
             }
+            Self::Copy(offset, source, length) => write!(
+                f,
+                "Copy(offset={:#x}, source={:#x}, length={:#x})",
+                offset, source, length
+            ),
         }
     }
 }
@@ -142,6 +160,11 @@ impl fmt::Display for BinaryDiffChunk {
                     stringify_bytes(bytes)
                 )
             }
+            Self::Copy(offset, source, length) => write!(
+                f,
+                "Copy   (offset={:#x}, source={:#x}, length={:#x})",
+                offset, source, length
+            ),
         }
     }
 }
@@ -0,0 +1,10 @@
+// How to resolve conflicts of BinaryDiff::merge3()
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ConflictResolution {
+    // Takes changes of `ours` on conflicting ranges
+    PreferOurs,
+    // Takes changes of `theirs` on conflicting ranges
+    PreferTheirs,
+    // Returns BinaryDiffError::ConflictError
+    Fail,
+}
@@ -0,0 +1,85 @@
+use super::DiffAlgorithm;
+use crate::binary_diff::algorithm::{get_delete_chunk, get_insert_chunk, get_same_chunk};
+use crate::binary_diff::binary_diff_chunk::BinaryDiffChunk;
+use crate::binary_diff::diff_options::DiffOptions;
+use crate::binary_diff::error::BinaryDiffError;
+use crate::binary_diff::helper::get_buffer_length;
+use crate::binary_diff::result::Result;
+use std::io::{BufReader, Cursor, Read, Seek};
+
+// Identifies diff chunks using greedy algorithm with LCS windows.
+// Fast but result is not always minimal.
+#[derive(Debug, Clone, Default)]
+pub struct GreedyAlgorithm {
+    options: DiffOptions,
+}
+
+impl GreedyAlgorithm {
+    pub fn new(options: &DiffOptions) -> Self {
+        Self {
+            options: options.clone(),
+        }
+    }
+}
+
+impl DiffAlgorithm for GreedyAlgorithm {
+    fn diff_slices(&self, old: &[u8], new: &[u8]) -> Result<Vec<BinaryDiffChunk>> {
+        // NOTE: Buffers are useless for bytes on memory, and discarding them on every seek is costly
+        self.diff(
+            &mut BufReader::with_capacity(0, Cursor::new(old)),
+            &mut BufReader::with_capacity(0, Cursor::new(new)),
+        )
+    }
+
+    fn diff<R1: Read + Seek, R2: Read + Seek>(
+        &self,
+        old: &mut BufReader<R1>,
+        new: &mut BufReader<R2>,
+    ) -> Result<Vec<BinaryDiffChunk>> {
+        let old_size = get_buffer_length(old)?;
+        let new_size = get_buffer_length(new)?;
+        log::trace!("[*] old_size, new_size = {}, {}", old_size, new_size);
+
+        let mut chunks = vec![];
+
+        loop {
+            let old_position = old.stream_position().map_err(BinaryDiffError::IoError)?;
+            let new_position = new.stream_position().map_err(BinaryDiffError::IoError)?;
+
+            if let Some(chunk) = get_same_chunk(old, new, old_size, new_size)? {
+                log::trace!("Added {:?}", chunk);
+                chunks.push(chunk);
+            }
+            if let Some(chunk) = get_delete_chunk(old, new, old_size, new_size, &self.options)? {
+                log::trace!("Added {:?}", chunk);
+                chunks.push(chunk);
+            }
+            if let Some(chunk) = get_insert_chunk(old, new, old_size, new_size, &self.options)? {
+                log::trace!("Added {:?}", chunk);
+                chunks.push(chunk);
+            }
+
+            let (current_old_position, current_new_position) = (
+                old.stream_position().map_err(BinaryDiffError::IoError)?,
+                new.stream_position().map_err(BinaryDiffError::IoError)?,
+            );
+            // Seek to end of both of buffers, so exit
+            if (old_size, new_size)
+                == (current_old_position as usize, current_new_position as usize)
+            {
+                break;
+            }
+            // Infinite loop detection
+            if (old_position, new_position) == (current_old_position, current_new_position) {
+                let err = Err(BinaryDiffError::InfiniteLoopError(
+                    old_position as usize,
+                    new_position as usize,
+                ));
+                log::error!("Detected infinite loop. There's a logic error: {:?}", err);
+                return err;
+            }
+        }
+
+        Ok(chunks)
+    }
+}
@@ -0,0 +1,41 @@
+use crate::binary_diff::binary_diff_chunk::BinaryDiffChunk;
+use crate::binary_diff::helper::{get_buffer_length, read_bytes};
+use crate::binary_diff::result::Result;
+use std::io::{BufReader, Read, Seek};
+
+mod greedy_algorithm;
+mod myers_algorithm;
+mod rolling_hash_algorithm;
+mod streaming_algorithm;
+mod suffix_array_algorithm;
+
+pub use greedy_algorithm::GreedyAlgorithm;
+pub use myers_algorithm::MyersAlgorithm;
+pub use rolling_hash_algorithm::RollingHashAlgorithm;
+pub use streaming_algorithm::StreamingAlgorithm;
+pub use suffix_array_algorithm::SuffixArrayAlgorithm;
+
+// Engine to calculate diff chunks used by BinaryDiff::with_algorithm().
+// Implementations should return chunks that satisfy requirements of BinaryDiff.chunks, and
+// that transform `old` into `new` with BinaryDiff::apply().
+pub trait DiffAlgorithm {
+    // Calculates diff chunks between binaries on memory
+    fn diff_slices(&self, old: &[u8], new: &[u8]) -> Result<Vec<BinaryDiffChunk>>;
+
+    // Calculates diff chunks between binaries. Reads whole of binaries by default.
+    // NOTE: `Self: Sized` keeps the trait usable as `dyn DiffAlgorithm` with diff_slices()
+    fn diff<R1: Read + Seek, R2: Read + Seek>(
+        &self,
+        old: &mut BufReader<R1>,
+        new: &mut BufReader<R2>,
+    ) -> Result<Vec<BinaryDiffChunk>>
+    where
+        Self: Sized,
+    {
+        let old_size = get_buffer_length(old)?;
+        let new_size = get_buffer_length(new)?;
+        let old_bytes = read_bytes(old, old_size)?;
+        let new_bytes = read_bytes(new, new_size)?;
+        self.diff_slices(&old_bytes, &new_bytes)
+    }
+}
@@ -0,0 +1,66 @@
+use super::{DiffAlgorithm, GreedyAlgorithm};
+use crate::binary_diff::algorithm::get_myers_chunks;
+use crate::binary_diff::binary_diff_chunk::BinaryDiffChunk;
+use crate::binary_diff::helper::{get_buffer_length, read_bytes};
+use crate::binary_diff::result::Result;
+use std::io::{BufReader, Read, Seek};
+
+// Identifies diff chunks using Myers' O(ND) algorithm. Result is minimal edit script.
+// Falls back to GreedyAlgorithm if size of either of binaries exceeds `max_size`.
+#[derive(Debug, Clone)]
+pub struct MyersAlgorithm {
+    max_size: usize,
+    fallback: GreedyAlgorithm,
+}
+
+impl MyersAlgorithm {
+    pub fn new(max_size: usize) -> Self {
+        Self {
+            max_size,
+            fallback: GreedyAlgorithm::default(),
+        }
+    }
+
+    pub fn fallback(mut self, fallback: GreedyAlgorithm) -> Self {
+        self.fallback = fallback;
+        self
+    }
+
+    fn exceeds_max_size(&self, old_size: usize, new_size: usize) -> bool {
+        if old_size > self.max_size || new_size > self.max_size {
+            log::info!(
+                "Binaries are too large for Myers engine. Fall back to greedy engine: old_size, new_size = {}, {}",
+                old_size,
+                new_size
+            );
+            true
+        } else {
+            false
+        }
+    }
+}
+
+impl DiffAlgorithm for MyersAlgorithm {
+    fn diff_slices(&self, old: &[u8], new: &[u8]) -> Result<Vec<BinaryDiffChunk>> {
+        if self.exceeds_max_size(old.len(), new.len()) {
+            return self.fallback.diff_slices(old, new);
+        }
+        Ok(get_myers_chunks(old, new))
+    }
+
+    fn diff<R1: Read + Seek, R2: Read + Seek>(
+        &self,
+        old: &mut BufReader<R1>,
+        new: &mut BufReader<R2>,
+    ) -> Result<Vec<BinaryDiffChunk>> {
+        let old_size = get_buffer_length(old)?;
+        let new_size = get_buffer_length(new)?;
+        if self.exceeds_max_size(old_size, new_size) {
+            return self.fallback.diff(old, new);
+        }
+
+        let old_bytes = read_bytes(old, old_size)?;
+        let new_bytes = read_bytes(new, new_size)?;
+        Ok(get_myers_chunks(&old_bytes, &new_bytes))
+    }
+}
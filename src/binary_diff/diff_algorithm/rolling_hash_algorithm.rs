@@ -0,0 +1,45 @@
+use super::{DiffAlgorithm, GreedyAlgorithm};
+use crate::binary_diff::algorithm::get_rolling_hash_chunks;
+use crate::binary_diff::binary_diff_chunk::BinaryDiffChunk;
+use crate::binary_diff::result::Result;
+use std::io::{BufReader, Cursor, Read, Seek};
+
+// Identifies diff chunks by matching fixed-size blocks with rolling checksum, like rsync.
+// Suitable for huge binaries since memory usage does not depend on size of binaries.
+// Changes between matched blocks are refined by `refinement` algorithm.
+#[derive(Debug, Clone)]
+pub struct RollingHashAlgorithm {
+    block_size: usize,
+    refinement: GreedyAlgorithm,
+}
+
+impl RollingHashAlgorithm {
+    pub fn new(block_size: usize) -> Self {
+        Self {
+            block_size,
+            refinement: GreedyAlgorithm::default(),
+        }
+    }
+
+    pub fn refinement(mut self, refinement: GreedyAlgorithm) -> Self {
+        self.refinement = refinement;
+        self
+    }
+}
+
+impl DiffAlgorithm for RollingHashAlgorithm {
+    fn diff_slices(&self, old: &[u8], new: &[u8]) -> Result<Vec<BinaryDiffChunk>> {
+        self.diff(
+            &mut BufReader::new(Cursor::new(old)),
+            &mut BufReader::new(Cursor::new(new)),
+        )
+    }
+
+    fn diff<R1: Read + Seek, R2: Read + Seek>(
+        &self,
+        old: &mut BufReader<R1>,
+        new: &mut BufReader<R2>,
+    ) -> Result<Vec<BinaryDiffChunk>> {
+        get_rolling_hash_chunks(old, new, self.block_size, &self.refinement)
+    }
+}
@@ -0,0 +1,46 @@
+use super::DiffAlgorithm;
+use crate::binary_diff::algorithm::get_streaming_chunks;
+use crate::binary_diff::binary_diff_chunk::BinaryDiffChunk;
+use crate::binary_diff::error::BinaryDiffError;
+use crate::binary_diff::result::Result;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+
+// Identifies diff chunks by reading binaries only once, so binaries need not to be seekable.
+// Changes are searched in at most `lookahead` bytes. Changes longer than that are not minimal.
+#[derive(Debug, Clone)]
+pub struct StreamingAlgorithm {
+    lookahead: usize,
+}
+
+impl StreamingAlgorithm {
+    pub fn new(lookahead: usize) -> Self {
+        Self { lookahead }
+    }
+
+    // Calculates diff chunks between binaries that are not seekable, e.g. pipes
+    pub fn diff_streams<R1: Read, R2: Read>(
+        &self,
+        old: R1,
+        new: R2,
+    ) -> Result<Vec<BinaryDiffChunk>> {
+        get_streaming_chunks(old, new, self.lookahead)
+    }
+}
+
+impl DiffAlgorithm for StreamingAlgorithm {
+    fn diff_slices(&self, old: &[u8], new: &[u8]) -> Result<Vec<BinaryDiffChunk>> {
+        self.diff_streams(old, new)
+    }
+
+    fn diff<R1: Read + Seek, R2: Read + Seek>(
+        &self,
+        old: &mut BufReader<R1>,
+        new: &mut BufReader<R2>,
+    ) -> Result<Vec<BinaryDiffChunk>> {
+        old.seek(SeekFrom::Start(0))
+            .map_err(BinaryDiffError::IoError)?;
+        new.seek(SeekFrom::Start(0))
+            .map_err(BinaryDiffError::IoError)?;
+        self.diff_streams(old, new)
+    }
+}
@@ -0,0 +1,23 @@
+use super::DiffAlgorithm;
+use crate::binary_diff::algorithm::get_suffix_array_chunks;
+use crate::binary_diff::binary_diff_chunk::BinaryDiffChunk;
+use crate::binary_diff::result::Result;
+
+// Identifies diff chunks from long matches found anywhere of original binary using suffix array.
+// Unlike GreedyAlgorithm, detects blocks inserted or moved far away in large binaries.
+#[derive(Debug, Clone)]
+pub struct SuffixArrayAlgorithm {
+    min_match_length: usize,
+}
+
+impl SuffixArrayAlgorithm {
+    pub fn new(min_match_length: usize) -> Self {
+        Self { min_match_length }
+    }
+}
+
+impl DiffAlgorithm for SuffixArrayAlgorithm {
+    fn diff_slices(&self, old: &[u8], new: &[u8]) -> Result<Vec<BinaryDiffChunk>> {
+        Ok(get_suffix_array_chunks(old, new, self.min_match_length))
+    }
+}
@@ -0,0 +1,146 @@
+use std::cmp::min;
+
+// Engines to calculate diff chunks
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum DiffEngine {
+    // Greedy search with LCS windows. Fast but result is not always minimal
+    Greedy,
+    // Myers' O(ND) algorithm. Result is minimal edit script
+    Myers,
+    // bsdiff-like search of long matches using suffix array. Detects blocks moved far away
+    SuffixArray,
+    // rsync-like matching of fixed-size blocks using rolling checksum. Suitable for huge binaries
+    RollingHash,
+    // Reads binaries only once with bounded lookahead. Binaries need not to be seekable
+    Streaming,
+}
+
+// Which chunk greedy engine chooses when both of Delete() and Insert() can be next chunk
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum TieBreaking {
+    PreferDelete,
+    PreferInsert,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct DiffOptions {
+    pub(crate) engine: DiffEngine,
+    pub(crate) myers_max_size: usize,
+    pub(crate) suffix_array_min_match_length: usize,
+    pub(crate) rolling_hash_block_size: usize,
+    pub(crate) streaming_lookahead: usize,
+    pub(crate) delete_windows: Vec<usize>,
+    pub(crate) insert_windows: Vec<usize>,
+    pub(crate) hash_granularity: Option<usize>,
+    pub(crate) min_match_length: usize,
+    pub(crate) tie_breaking: TieBreaking,
+    pub(crate) max_lookahead: usize,
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        Self {
+            engine: DiffEngine::Greedy,
+            myers_max_size: 0x10000,
+            // NOTE: Shorter matches are likely to be coincidence in large binaries
+            suffix_array_min_match_length: 8,
+            rolling_hash_block_size: 0x400,
+            streaming_lookahead: 0x10000,
+            // NOTE: More values of the window are fine grained, more Delete() chunk become precise.
+            delete_windows: vec![6, 8, 16, 32, 64],
+            // NOTE: window=2 is required to detect Insert(offset, length=1) chunk
+            insert_windows: vec![4, 8, 16, 32, 2],
+            hash_granularity: None,
+            min_match_length: 1,
+            // To decrease number of Insert() chunk
+            tie_breaking: TieBreaking::PreferDelete,
+            max_lookahead: usize::MAX,
+        }
+    }
+}
+
+impl DiffOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn engine(mut self, engine: DiffEngine) -> Self {
+        self.engine = engine;
+        self
+    }
+
+    // DiffEngine::Myers falls back to DiffEngine::Greedy if size of either of binaries exceeds this value
+    pub fn myers_max_size(mut self, size: usize) -> Self {
+        self.myers_max_size = size;
+        self
+    }
+
+    // DiffEngine::SuffixArray ignores common bytes shorter than this value
+    pub fn suffix_array_min_match_length(mut self, length: usize) -> Self {
+        self.suffix_array_min_match_length = length;
+        self
+    }
+
+    // Size of blocks that DiffEngine::RollingHash matches.
+    // Blocks become larger than this value for huge binaries to bound memory usage.
+    pub fn rolling_hash_block_size(mut self, size: usize) -> Self {
+        self.rolling_hash_block_size = size;
+        self
+    }
+
+    // Bytes that DiffEngine::Streaming reads ahead to search end of changes. At least 0x40 bytes are read ahead
+    pub fn streaming_lookahead(mut self, lookahead: usize) -> Self {
+        self.streaming_lookahead = lookahead;
+        self
+    }
+
+    // Windows to search next Same() chunk after Delete() chunk. Searched in given order
+    pub fn delete_windows(mut self, windows: &[usize]) -> Self {
+        self.delete_windows = windows.to_vec();
+        self
+    }
+
+    // Windows to search next Same() chunk after Insert() chunk. Searched in given order
+    pub fn insert_windows(mut self, windows: &[usize]) -> Self {
+        self.insert_windows = windows.to_vec();
+        self
+    }
+
+    // Hash size of LCS search in windows. Shrunk to bytes in windows near the end of binaries.
+    // Defaults to 1 for windows up to 16 bytes and 2 for wider windows.
+    pub fn hash_granularity(mut self, granularity: usize) -> Self {
+        self.hash_granularity = Some(granularity);
+        self
+    }
+
+    // Common bytes shorter than this value in windows are not regarded as Same() chunk
+    pub fn min_match_length(mut self, length: usize) -> Self {
+        self.min_match_length = length;
+        self
+    }
+
+    pub fn tie_breaking(mut self, tie_breaking: TieBreaking) -> Self {
+        self.tie_breaking = tie_breaking;
+        self
+    }
+
+    // Windows wider than this value are shrunk to this value
+    pub fn max_lookahead(mut self, length: usize) -> Self {
+        self.max_lookahead = length;
+        self
+    }
+
+    // `length` is the shorter length of bytes in window
+    pub(crate) fn hash_size(&self, window: usize, length: usize) -> usize {
+        match self.hash_granularity {
+            Some(granularity) => min(granularity, length),
+            None => {
+                if window <= 16 {
+                    1
+                } else {
+                    2
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,176 @@
+use crate::binary_diff::binary_diff_chunk::BinaryDiffChunk;
+
+// Statistics of chunks of BinaryDiff, e.g. to sort fuzzer outputs by how far they drifted from their seed
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct DiffStats {
+    pub(crate) original_length: usize,
+    pub(crate) patched_length: usize,
+    // Bytes of Same() chunks
+    pub(crate) kept_bytes: usize,
+    // Bytes of payload of Insert() chunks
+    pub(crate) inserted_bytes: usize,
+    // Bytes of original binary removed by Delete() chunks
+    pub(crate) deleted_bytes: usize,
+    // Bytes of original binary removed by Replace() chunks
+    pub(crate) replaced_bytes: usize,
+    // Bytes of Copy() chunks
+    pub(crate) copied_bytes: usize,
+    pub(crate) same_chunks: usize,
+    pub(crate) insert_chunks: usize,
+    pub(crate) delete_chunks: usize,
+    pub(crate) replace_chunks: usize,
+    pub(crate) copy_chunks: usize,
+    pub(crate) longest_same_length: usize,
+}
+
+impl DiffStats {
+    pub(crate) fn new(chunks: &[BinaryDiffChunk]) -> Self {
+        let mut stats = Self::default();
+        for chunk in chunks.iter() {
+            stats.original_length += chunk.original_length();
+            stats.patched_length += chunk.patched_length();
+            match chunk {
+                BinaryDiffChunk::Same(_, length) => {
+                    stats.kept_bytes += length;
+                    stats.same_chunks += 1;
+                    stats.longest_same_length = std::cmp::max(stats.longest_same_length, *length);
+                }
+                BinaryDiffChunk::Insert(_, bytes) => {
+                    stats.inserted_bytes += bytes.len();
+                    stats.insert_chunks += 1;
+                }
+                BinaryDiffChunk::Delete(_, length) => {
+                    stats.deleted_bytes += length;
+                    stats.delete_chunks += 1;
+                }
+                BinaryDiffChunk::Replace(_, length, _) => {
+                    stats.replaced_bytes += length;
+                    stats.replace_chunks += 1;
+                }
+                BinaryDiffChunk::Copy(_, _, length) => {
+                    stats.copied_bytes += length;
+                    stats.copy_chunks += 1;
+                }
+            }
+        }
+        stats
+    }
+
+    pub fn original_length(&self) -> usize {
+        self.original_length
+    }
+
+    pub fn patched_length(&self) -> usize {
+        self.patched_length
+    }
+
+    pub fn kept_bytes(&self) -> usize {
+        self.kept_bytes
+    }
+
+    pub fn inserted_bytes(&self) -> usize {
+        self.inserted_bytes
+    }
+
+    pub fn deleted_bytes(&self) -> usize {
+        self.deleted_bytes
+    }
+
+    pub fn replaced_bytes(&self) -> usize {
+        self.replaced_bytes
+    }
+
+    pub fn copied_bytes(&self) -> usize {
+        self.copied_bytes
+    }
+
+    pub fn same_chunks(&self) -> usize {
+        self.same_chunks
+    }
+
+    pub fn insert_chunks(&self) -> usize {
+        self.insert_chunks
+    }
+
+    pub fn delete_chunks(&self) -> usize {
+        self.delete_chunks
+    }
+
+    pub fn replace_chunks(&self) -> usize {
+        self.replace_chunks
+    }
+
+    pub fn copy_chunks(&self) -> usize {
+        self.copy_chunks
+    }
+
+    // Longest run of bytes unchanged
+    pub fn longest_same_length(&self) -> usize {
+        self.longest_same_length
+    }
+
+    // Bytes removed from original binary plus bytes introduced to patched binary
+    pub fn edit_distance(&self) -> usize {
+        self.original_length + self.patched_length - 2 * self.kept_bytes
+    }
+
+    // Edit distance divided by total length of both binaries, in 0.0..=1.0
+    pub fn normalized_edit_distance(&self) -> f64 {
+        match self.original_length + self.patched_length {
+            0 => 0.0,
+            total => self.edit_distance() as f64 / total as f64,
+        }
+    }
+
+    // 1.0 if binaries are the same, and 0.0 if they have no bytes in common
+    // NOTE: Equals to 1.0 - normalized_edit_distance()
+    pub fn similarity(&self) -> f64 {
+        match self.original_length + self.patched_length {
+            0 => 1.0,
+            total => (2 * self.kept_bytes) as f64 / total as f64,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::BinaryDiff;
+    use crate::BinaryDiffChunk::{Copy, Delete, Insert, Replace, Same};
+
+    #[test]
+    fn test_diff_stats() {
+        let diff = BinaryDiff::from(&vec![
+            Same(0, 4),
+            Delete(4, 2),
+            Insert(6, vec![0, 1, 2]),
+            Same(6, 6),
+            Replace(12, 2, vec![3]),
+            Copy(14, 0, 2),
+        ]);
+        let stats = diff.stats();
+        assert_eq!(stats.original_length(), 14);
+        assert_eq!(stats.patched_length(), 16);
+        assert_eq!(stats.kept_bytes(), 10);
+        assert_eq!(stats.inserted_bytes(), 3);
+        assert_eq!(stats.deleted_bytes(), 2);
+        assert_eq!(stats.replaced_bytes(), 2);
+        assert_eq!(stats.copied_bytes(), 2);
+        assert_eq!(
+            (
+                stats.same_chunks(),
+                stats.insert_chunks(),
+                stats.delete_chunks(),
+                stats.replace_chunks(),
+                stats.copy_chunks()
+            ),
+            (2, 1, 1, 1, 1)
+        );
+        assert_eq!(stats.longest_same_length(), 6);
+        assert_eq!(stats.edit_distance(), 10);
+        assert_eq!(stats.similarity(), 2.0 / 3.0);
+
+        let stats = BinaryDiff::from(&vec![]).stats();
+        assert_eq!(stats.edit_distance(), 0);
+        assert_eq!(stats.similarity(), 1.0);
+    }
+}
@@ -1,7 +1,24 @@
+use crate::binary_diff::binary_diff_chunk::BinaryDiffChunk;
+use std::ops::Range;
+
 type Offset = usize;
+type Length = usize;
 
+#[allow(clippy::enum_variant_names)]
 #[derive(Debug)]
 pub enum BinaryDiffError {
     IoError(std::io::Error),
     InfiniteLoopError(Offset, Offset),
+    // (expected offset, chunk): Chunk does not start at where the previous one ends
+    ChunkOffsetMismatchError(Offset, BinaryDiffChunk),
+    // (size of original binary, chunk): Chunk exceeds the end of original binary
+    ChunkOutOfRangeError(Length, BinaryDiffChunk),
+    // (end of chunks, size of original binary): Chunks do not cover whole original binary
+    IncompleteChunksError(Offset, Length),
+    // Conflicting ranges in offset of base binary of BinaryDiff::merge3()
+    ConflictError(Vec<Range<Offset>>),
+    // Chunk that neither deletes nor inserts any bytes
+    EmptyChunkError(BinaryDiffChunk),
+    // (previous chunk, chunk): Adjacent chunks that should be merged into one
+    UnmergedChunksError(BinaryDiffChunk, BinaryDiffChunk),
 }
@@ -0,0 +1,28 @@
+use super::get_hash_match_spec;
+use bcmp::{longest_common_substring, AlgoSpec, Match};
+use std::cmp::min;
+
+// Finds the longest common substring of both bytes with hash of `hash_size` bytes.
+// Bytes shorter than the hash have no common substring.
+// NOTE: bcmp overflows on debug build instead of returning no match for such bytes
+pub fn find_longest_common_substring(first: &[u8], second: &[u8], hash_size: usize) -> Match {
+    match get_hash_match_spec(hash_size) {
+        AlgoSpec::HashMatch(size) if size > min(first.len(), second.len()) => Match::new(0, 0, 0),
+        spec => longest_common_substring(first, second, spec),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::find_longest_common_substring;
+
+    #[test]
+    fn test_find_longest_common_substring() {
+        let lcs = find_longest_common_substring(&[0, 1, 2, 3], &[2, 3, 0], 2);
+        assert_eq!((lcs.first_pos, lcs.second_pos, lcs.length), (2, 0, 2));
+        let lcs = find_longest_common_substring(&[2], &[1, 2, 3], 1);
+        assert_eq!((lcs.first_pos, lcs.second_pos, lcs.length), (0, 1, 1));
+        assert_eq!(find_longest_common_substring(&[2], &[1, 2, 3], 2).length, 0);
+        assert_eq!(find_longest_common_substring(&[1, 2, 3], &[], 1).length, 0);
+    }
+}
@@ -0,0 +1,43 @@
+use bcmp::AlgoSpec;
+
+// Hash sizes supported by bcmp::AlgoSpec::HashMatch
+const SUPPORTED_HASH_SIZES: [usize; 20] = [
+    1, 2, 3, 4, 5, 6, 7, 8, 10, 12, 14, 16, 20, 24, 28, 32, 40, 48, 56, 64,
+];
+
+// Returns the largest supported hash size not exceeding `hash_size`.
+// bcmp panics if hash size is unsupported.
+pub fn get_hash_match_spec(hash_size: usize) -> AlgoSpec {
+    let hash_size = SUPPORTED_HASH_SIZES
+        .iter()
+        .rev()
+        .find(|size| **size <= hash_size)
+        .copied()
+        .unwrap_or(1);
+    AlgoSpec::HashMatch(hash_size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::get_hash_match_spec;
+    use bcmp::{longest_common_substring, AlgoSpec};
+
+    #[test]
+    fn test_get_hash_match_spec() {
+        assert!(matches!(get_hash_match_spec(2), AlgoSpec::HashMatch(2)));
+        assert!(matches!(get_hash_match_spec(9), AlgoSpec::HashMatch(8)));
+        assert!(matches!(get_hash_match_spec(100), AlgoSpec::HashMatch(64)));
+        assert!(matches!(get_hash_match_spec(0), AlgoSpec::HashMatch(1)));
+    }
+
+    #[test]
+    fn test_unsupported_hash_size() {
+        let bytes = [0u8; 16];
+        let result = std::panic::catch_unwind(|| {
+            longest_common_substring(&bytes, &bytes, AlgoSpec::HashMatch(9))
+        });
+        assert!(result.is_err());
+        let result = longest_common_substring(&bytes, &bytes, get_hash_match_spec(9));
+        assert_eq!(result.length, 16);
+    }
+}
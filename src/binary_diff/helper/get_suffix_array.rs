@@ -0,0 +1,70 @@
+// Returns start offsets of all suffixes of `bytes` in lexicographical order.
+// NOTE: Uses prefix doubling. Takes O(n log^2 n) time and O(n) words of memory.
+pub fn get_suffix_array(bytes: &[u8]) -> Vec<usize> {
+    let n = bytes.len();
+    // Initial ranks by first 3 bytes. End of bytes is ranked lower than any byte
+    let mut k = 3;
+    let mut rank: Vec<usize> = (0..n)
+        .map(|i| {
+            (i..i + k).fold(0, |rank, j| {
+                rank * 0x101 + bytes.get(j).map_or(0, |byte| *byte as usize + 1)
+            })
+        })
+        .collect();
+    // (rank of first k bytes, rank of next k bytes, offset)
+    let mut suffixes: Vec<(usize, usize, usize)> = Vec::with_capacity(n);
+
+    loop {
+        suffixes.clear();
+        suffixes.extend((0..n).map(|i| (rank[i], rank.get(i + k).map_or(0, |rank| rank + 1), i)));
+        suffixes.sort_unstable();
+
+        let mut distinct_count = 0;
+        for j in 0..n {
+            if j > 0 && (suffixes[j - 1].0, suffixes[j - 1].1) != (suffixes[j].0, suffixes[j].1) {
+                distinct_count += 1;
+            }
+            rank[suffixes[j].2] = distinct_count;
+        }
+
+        // All suffixes are distinguished
+        if distinct_count + 1 >= n {
+            break;
+        }
+        k *= 2;
+    }
+
+    suffixes.into_iter().map(|(_, _, i)| i).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::get_suffix_array;
+    use crate::test_helper::random_bytes;
+
+    #[test]
+    fn test_get_suffix_array() {
+        assert_eq!(get_suffix_array(b""), Vec::<usize>::new());
+        assert_eq!(get_suffix_array(b"a"), vec![0]);
+        assert_eq!(get_suffix_array(b"banana"), vec![5, 3, 1, 0, 4, 2]);
+        assert_eq!(
+            get_suffix_array(&[0, 0, 0, 0, 0, 0]),
+            vec![5, 4, 3, 2, 1, 0]
+        );
+        assert_eq!(get_suffix_array(&[1, 0, 1, 0]), vec![3, 1, 2, 0]);
+    }
+
+    #[test]
+    fn test_get_suffix_array_equals_to_naive_sort() {
+        let mut seed = 0x1234_5678u32;
+        for length in 0..100 {
+            let bytes: Vec<u8> = random_bytes(&mut seed, length)
+                .iter()
+                .map(|byte| byte % 3)
+                .collect();
+            let mut expected: Vec<usize> = (0..length).collect();
+            expected.sort_by_key(|i| &bytes[*i..]);
+            assert_eq!(get_suffix_array(&bytes), expected);
+        }
+    }
+}
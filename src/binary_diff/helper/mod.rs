@@ -1,9 +1,15 @@
 mod find;
+mod find_longest_common_substring;
 mod get_buffer_length;
+mod get_hash_match_spec;
+mod get_suffix_array;
 mod read_bytes;
 mod read_one_byte;
 
 pub(crate) use find::find;
+pub(crate) use find_longest_common_substring::find_longest_common_substring;
 pub(crate) use get_buffer_length::get_buffer_length;
+pub(crate) use get_hash_match_spec::get_hash_match_spec;
+pub(crate) use get_suffix_array::get_suffix_array;
 pub(crate) use read_bytes::read_bytes;
 pub(crate) use read_one_byte::read_one_byte;
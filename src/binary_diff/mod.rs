@@ -1,23 +1,42 @@
 use crate::binary_diff::binary_diff_chunk::BinaryDiffChunk;
-use crate::binary_diff::error::BinaryDiffError;
+use crate::binary_diff::conflict_resolution::ConflictResolution;
+use crate::binary_diff::diff_algorithm::{
+    DiffAlgorithm, GreedyAlgorithm, MyersAlgorithm, RollingHashAlgorithm, StreamingAlgorithm,
+    SuffixArrayAlgorithm,
+};
+use crate::binary_diff::diff_options::{DiffEngine, DiffOptions};
+use crate::binary_diff::diff_stats::DiffStats;
+use crate::binary_diff::positioned_chunk::PositionedChunk;
 use result::Result;
-use std::io::{BufReader, Read, Seek};
+use std::io::{BufReader, Cursor, Read, Seek, Write};
+use std::ops::Range;
 // use alloc::vec::Vec;
 
 pub(crate) mod binary_diff_chunk;
+pub(crate) mod conflict_resolution;
+pub(crate) mod diff_algorithm;
+pub(crate) mod diff_options;
+pub(crate) mod diff_stats;
 pub mod error;
+pub(crate) mod positioned_chunk;
 pub mod result;
+#[cfg(feature = "serde")]
+mod serialization;
 
 // Internal use only
 mod algorithm;
 mod helper;
 
-use algorithm::{get_delete_chunk, get_insert_chunk, get_same_chunk, introduce_replace_chunk};
-use helper::get_buffer_length;
+use algorithm::{
+    apply_chunks, compose_chunks, introduce_replace_chunk, invert_chunks, merge3_chunks,
+    merge_chunks, validate_chunks,
+};
 
 #[derive(Debug, Eq, PartialEq)]
 pub struct BinaryDiff {
     chunks: Vec<BinaryDiffChunk>,
+    // Offset in patched binary of each of `chunks`
+    patched_offsets: Vec<usize>,
 }
 
 impl BinaryDiff {
@@ -25,78 +44,202 @@ impl BinaryDiff {
     //   - Minimize the length of the return value
     //   - An chunk and its next one is NOT the same
     //   - Sorted `offset` of chunk(offset, ...) accenting
-    pub fn new<R: Read + Seek>(old: &mut BufReader<R>, new: &mut BufReader<R>) -> Result<Self> {
-        let old_size = get_buffer_length(old)?;
-        let new_size = get_buffer_length(new)?;
-        log::trace!("[*] old_size, new_size = {}, {}", old_size, new_size);
-
-        let mut chunks = vec![];
-
-        // Identify diff chunks using greedy algorithm
-        loop {
-            let old_position = old.stream_position().map_err(BinaryDiffError::IoError)?;
-            let new_position = new.stream_position().map_err(BinaryDiffError::IoError)?;
+    pub fn new<R1: Read + Seek, R2: Read + Seek>(
+        old: &mut BufReader<R1>,
+        new: &mut BufReader<R2>,
+    ) -> Result<Self> {
+        Self::with_algorithm(old, new, &GreedyAlgorithm::default())
+    }
 
-            if let Some(chunk) = get_same_chunk(old, new, old_size, new_size)? {
-                log::trace!("Added {:?}", chunk);
-                chunks.push(chunk);
-            }
-            if let Some(chunk) = get_delete_chunk(old, new, old_size, new_size)? {
-                log::trace!("Added {:?}", chunk);
-                chunks.push(chunk);
-            }
-            if let Some(chunk) = get_insert_chunk(old, new, old_size, new_size)? {
-                log::trace!("Added {:?}", chunk);
-                chunks.push(chunk);
-            }
+    // Calculates diff between binaries on memory. Same result as BinaryDiff::new() with readers of them
+    pub fn from_slices(old: &[u8], new: &[u8]) -> Result<Self> {
+        Ok(Self::with_chunks(
+            GreedyAlgorithm::default().diff_slices(old, new)?,
+        ))
+    }
 
-            let (current_old_position, current_new_position) = (
-                old.stream_position().map_err(BinaryDiffError::IoError)?,
-                new.stream_position().map_err(BinaryDiffError::IoError)?,
-            );
-            // Seek to end of both of buffers, so exit
-            if (old_size, new_size)
-                == (current_old_position as usize, current_new_position as usize)
-            {
-                break;
-            }
-            // Infinite loop detection
-            if (old_position, new_position) == (current_old_position, current_new_position) {
-                let err = Err(BinaryDiffError::InfiniteLoopError(
-                    old_position as usize,
-                    new_position as usize,
-                ));
-                log::error!("Detected infinite loop. There's a logic error: {:?}", err);
-                return err;
-            }
+    pub fn new_with_options<R1: Read + Seek, R2: Read + Seek>(
+        old: &mut BufReader<R1>,
+        new: &mut BufReader<R2>,
+        options: &DiffOptions,
+    ) -> Result<Self> {
+        let greedy = GreedyAlgorithm::new(options);
+        match options.engine {
+            DiffEngine::Greedy => Self::with_algorithm(old, new, &greedy),
+            DiffEngine::Myers => Self::with_algorithm(
+                old,
+                new,
+                &MyersAlgorithm::new(options.myers_max_size).fallback(greedy),
+            ),
+            DiffEngine::SuffixArray => Self::with_algorithm(
+                old,
+                new,
+                &SuffixArrayAlgorithm::new(options.suffix_array_min_match_length),
+            ),
+            DiffEngine::RollingHash => Self::with_algorithm(
+                old,
+                new,
+                &RollingHashAlgorithm::new(options.rolling_hash_block_size).refinement(greedy),
+            ),
+            DiffEngine::Streaming => Self::with_algorithm(
+                old,
+                new,
+                &StreamingAlgorithm::new(options.streaming_lookahead),
+            ),
         }
+    }
 
-        Ok(Self { chunks })
+    // Calculates diff between binaries that are not seekable (e.g. pipes) using streaming engine.
+    // `options.engine` is ignored.
+    pub fn new_streaming<R1: Read, R2: Read>(
+        old: R1,
+        new: R2,
+        options: &DiffOptions,
+    ) -> Result<Self> {
+        Ok(Self::with_chunks(
+            StreamingAlgorithm::new(options.streaming_lookahead).diff_streams(old, new)?,
+        ))
     }
 
-    pub fn enhance(&self) -> Self {
+    // Calculates diff using given engine
+    pub fn with_algorithm<R1: Read + Seek, R2: Read + Seek, A: DiffAlgorithm>(
+        old: &mut BufReader<R1>,
+        new: &mut BufReader<R2>,
+        algorithm: &A,
+    ) -> Result<Self> {
+        Ok(Self::with_chunks(algorithm.diff(old, new)?))
+    }
+
+    fn with_chunks(chunks: Vec<BinaryDiffChunk>) -> Self {
+        let patched_offsets = chunks
+            .iter()
+            .scan(0, |patched_offset, chunk| {
+                let offset = *patched_offset;
+                *patched_offset += chunk.patched_length();
+                Some(offset)
+            })
+            .collect();
         Self {
-            chunks: introduce_replace_chunk(&self.chunks),
+            chunks,
+            patched_offsets,
         }
     }
 
+    pub fn enhance(&self) -> Self {
+        Self::with_chunks(introduce_replace_chunk(&self.chunks))
+    }
+
+    // Merges adjacent chunks of the same kind and changes between two Same() chunks into one
+    // Delete() or Replace() chunk followed by Insert() and Copy() chunks, so that the result
+    // satisfies the requirements of BinaryDiff.chunks (see validate())
+    pub fn normalize(&self) -> Self {
+        Self::with_chunks(introduce_replace_chunk(&merge_chunks(&self.chunks)))
+    }
+
+    // Checks that chunks are contiguous, have some length and cannot be merged any further.
+    // Returns BinaryDiffError::ChunkOffsetMismatchError, EmptyChunkError or UnmergedChunksError otherwise.
+    pub fn validate(&self) -> Result<()> {
+        validate_chunks(&self.chunks)
+    }
+
+    #[allow(clippy::ptr_arg)]
     pub fn from(unsorted_chunks: &Vec<BinaryDiffChunk>) -> Self {
         let mut chunks = unsorted_chunks.to_vec();
         chunks.sort();
-        Self { chunks }
+        Self::with_chunks(chunks)
     }
 
     pub fn chunks(&self) -> &Vec<BinaryDiffChunk> {
         &self.chunks
     }
+
+    // Iterates chunks with their ranges in original binary and in patched binary
+    pub fn iter_with_positions(&self) -> impl Iterator<Item = PositionedChunk<'_>> {
+        self.chunks
+            .iter()
+            .zip(self.patched_offsets.iter())
+            .map(|(chunk, patched_offset)| PositionedChunk {
+                chunk,
+                patched_offset: *patched_offset,
+            })
+    }
+
+    pub fn stats(&self) -> DiffStats {
+        DiffStats::new(&self.chunks)
+    }
+
+    // Keeps changes (i.e. chunks other than Same() chunks) at `selected` indices of chunks and reverts the others.
+    // `selected` must be sorted.
+    pub(crate) fn select_changes(&self, selected: &[usize]) -> Self {
+        let chunks: Vec<BinaryDiffChunk> = self
+            .chunks
+            .iter()
+            .enumerate()
+            .filter_map(|(index, chunk)| match chunk {
+                BinaryDiffChunk::Same(_, _) => Some(chunk.clone()),
+                _ if selected.binary_search(&index).is_ok() => Some(chunk.clone()),
+                BinaryDiffChunk::Delete(offset, length)
+                | BinaryDiffChunk::Replace(offset, length, _) => {
+                    Some(BinaryDiffChunk::Same(*offset, *length))
+                }
+                BinaryDiffChunk::Insert(_, _) | BinaryDiffChunk::Copy(_, _, _) => None,
+            })
+            .collect();
+        Self::with_chunks(merge_chunks(&chunks))
+    }
+
+    // Reconstructs patched binary by applying chunks to `old`, and writes it to `out`
+    pub fn apply<R: Read + Seek, W: Write>(&self, old: R, mut out: W) -> Result<()> {
+        apply_chunks(&self.chunks, &mut BufReader::new(old), &mut out)
+    }
+
+    pub fn apply_slice(&self, old: &[u8]) -> Result<Vec<u8>> {
+        let mut patched = vec![];
+        self.apply(Cursor::new(old), &mut patched)?;
+        Ok(patched)
+    }
+
+    // Returns diff that transforms patched binary back into `old`
+    pub fn invert(&self, old: &[u8]) -> Result<Self> {
+        Ok(Self::with_chunks(invert_chunks(&self.chunks, old)?))
+    }
+
+    // Returns diff that transforms original binary of `self` into patched binary of `next`.
+    // Patched binary of `self` must be original binary of `next`.
+    pub fn compose(&self, next: &BinaryDiff) -> Result<Self> {
+        let chunks = compose_chunks(&self.chunks, &next.chunks)?;
+        Ok(Self::with_chunks(introduce_replace_chunk(&merge_chunks(
+            &chunks,
+        ))))
+    }
+
+    // Merges `ours` and `theirs` both of which are made from `base`.
+    // Returns merged diff and conflicting ranges in offset of `base`.
+    pub fn merge3(
+        base: &[u8],
+        ours: &BinaryDiff,
+        theirs: &BinaryDiff,
+        resolution: ConflictResolution,
+    ) -> Result<(Self, Vec<Range<usize>>)> {
+        let (chunks, conflicts) =
+            merge3_chunks(base, &ours.chunks, &theirs.chunks, resolution)?;
+        let merged = Self::with_chunks(introduce_replace_chunk(&merge_chunks(&chunks)));
+        Ok((merged, conflicts))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     extern crate env_logger;
-    use crate::binary_diff::binary_diff_chunk::BinaryDiffChunk::{Delete, Insert, Same};
+    use crate::binary_diff::binary_diff_chunk::BinaryDiffChunk;
+    use crate::binary_diff::binary_diff_chunk::BinaryDiffChunk::{Delete, Insert, Replace, Same};
+    use crate::binary_diff::conflict_resolution::ConflictResolution;
+    use crate::binary_diff::diff_algorithm::{DiffAlgorithm, GreedyAlgorithm};
+    use crate::binary_diff::diff_options::{DiffEngine, DiffOptions, TieBreaking};
+    use crate::binary_diff::error::BinaryDiffError;
     use crate::binary_diff::result::Result;
     use crate::binary_diff::BinaryDiff;
+    use crate::test_helper::random_bytes;
     use std::io::{BufReader, Cursor};
 
     fn init() {
@@ -340,6 +483,57 @@ mod tests {
     }
 
     #[test]
+    fn test_normalize() {
+        init();
+
+        let diff = BinaryDiff::from(&vec![
+            Same(0x0, 0x6),
+            Delete(0x6, 0x2),
+            Same(0x8, 0x2),
+            Delete(0xa, 0x1),
+            Insert(0xb, vec![0x18]),
+            Insert(0xb, vec![0x03, 0x18]),
+            Same(0xb, 0x1),
+            Same(0xc, 0x14),
+        ]);
+        match diff.validate() {
+            Err(BinaryDiffError::UnmergedChunksError(previous, chunk)) => {
+                assert_eq!(previous, Insert(0xb, vec![0x18]));
+                assert_eq!(chunk, Insert(0xb, vec![0x03, 0x18]));
+            }
+            other => panic!("{:?}", other),
+        }
+
+        let normalized = diff.normalize();
+        assert!(normalized.validate().is_ok());
+        assert_eq!(
+            normalized.chunks(),
+            &vec![
+                Same(0x0, 0x6),
+                Delete(0x6, 0x2),
+                Same(0x8, 0x2),
+                Replace(0xa, 0x1, vec![0x18, 0x03, 0x18]),
+                Same(0xb, 0x15),
+            ]
+        );
+        assert_eq!(normalized.normalize(), normalized);
+
+        // Delete() + Insert() + Delete() collapses into one Replace()
+        let diff = BinaryDiff::from(&vec![
+            Delete(0, 1),
+            Insert(1, vec![0xff]),
+            Delete(1, 2),
+            Same(3, 1),
+        ]);
+        assert!(diff.validate().is_err());
+        assert_eq!(
+            diff.normalize().chunks(),
+            &vec![Replace(0, 3, vec![0xff]), Same(3, 1)]
+        );
+    }
+
+    #[test]
+    #[allow(clippy::zero_prefixed_literal)]
     fn test_crash_minimization() {
         init();
 
@@ -386,6 +580,544 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_apply() {
+        init();
+
+        let samples = vec![
+            (vec![0, 1, 2, 3], vec![0, 1]),
+            (vec![0, 1], vec![0, 1, 2, 3]),
+            (vec![0, 1, 4], vec![2, 3, 4]),
+            (vec![1, 2, 0, 0], vec![0, 3, 0, 0]),
+            (vec![0, 1, 2], vec![2, 3, 4]),
+            (vec![], vec![0, 1]),
+            (
+                std::fs::read("tests/samples/binary/seeds/573a46286deaf9df81fb90d7b786708d845b5f23")
+                    .unwrap(),
+                std::fs::read("tests/samples/binary/seeds/c298122410da09836c59484e995c287294c31394")
+                    .unwrap(),
+            ),
+            (
+                std::fs::read("tests/samples/binary/crash-minimization/crash-235641cefe524570bf0df6a3b3722535ce2dbbf7")
+                    .unwrap(),
+                std::fs::read("tests/samples/binary/crash-minimization/minimized-from-10dad543216eabe6d97b9d0ba8459215f6dca3f3")
+                    .unwrap(),
+            ),
+        ];
+        for (old, new) in samples {
+            let diff = binary_diff_wrapper(&old, &new).unwrap();
+            log::trace!("[*] diff() = {:?}", diff);
+            assert_eq!(diff.apply_slice(&old).unwrap(), new);
+            assert_eq!(diff.enhance().apply_slice(&old).unwrap(), new);
+        }
+    }
+
+    #[test]
+    fn test_invert() {
+        init();
+
+        let samples = vec![
+            (vec![0, 1, 2, 3], vec![0, 1]),
+            (vec![0x00, 0x0b, 0x01, 0x00, 0x03], vec![0x00, 0x0b, 0x01, 0xfd, 0x03]),
+            (vec![0, 1, 2], vec![2, 3, 4]),
+            (
+                std::fs::read("tests/samples/binary/crash-minimization/crash-235641cefe524570bf0df6a3b3722535ce2dbbf7")
+                    .unwrap(),
+                std::fs::read("tests/samples/binary/crash-minimization/minimized-from-10dad543216eabe6d97b9d0ba8459215f6dca3f3")
+                    .unwrap(),
+            ),
+        ];
+        for (old, new) in samples {
+            let diff = binary_diff_wrapper(&old, &new).unwrap();
+            for diff in [diff.enhance(), diff] {
+                let inverted = diff.invert(&old).unwrap();
+                log::trace!("[*] invert() = {:?}", inverted);
+                assert_eq!(inverted.apply_slice(&new).unwrap(), old);
+                assert_eq!(inverted.invert(&new).unwrap(), diff);
+            }
+        }
+    }
+
+    #[test]
+    fn test_compose() {
+        init();
+
+        let lineage = [
+            std::fs::read("tests/samples/binary/seeds/573a46286deaf9df81fb90d7b786708d845b5f23")
+                .unwrap(),
+            std::fs::read("tests/samples/binary/seeds/c298122410da09836c59484e995c287294c31394")
+                .unwrap(),
+            vec![
+                0x2e, 0x03, 0x00, 0x00, 0x18, 0x03, 0x00, 0xfe, 0x00, 0x03, 0x1d, 0xda,
+            ],
+            vec![],
+            vec![0x00, 0x01],
+        ];
+        for files in lineage.windows(3) {
+            let (a, b, c) = (&files[0], &files[1], &files[2]);
+            let composed = binary_diff_wrapper(a, b)
+                .unwrap()
+                .compose(&binary_diff_wrapper(b, c).unwrap())
+                .unwrap();
+            log::trace!("[*] compose() = {:?}", composed);
+            assert_eq!(composed.apply_slice(a).unwrap(), *c);
+        }
+    }
+
+    #[test]
+    fn test_compose_equals_to_fresh_diff() {
+        init();
+
+        let a = vec![0, 1, 2, 3, 4, 5, 6, 7];
+        let b = vec![0, 1, 0xff, 4, 5, 6, 7];
+        let c = vec![0, 1, 0xff, 4, 5, 6, 7, 8];
+        let composed = binary_diff_wrapper(&a, &b)
+            .unwrap()
+            .compose(&binary_diff_wrapper(&b, &c).unwrap())
+            .unwrap();
+        assert_eq!(composed, binary_diff_wrapper(&a, &c).unwrap().enhance());
+    }
+
+    #[test]
+    fn test_merge3() {
+        init();
+
+        let base = vec![
+            0x2e, 0x03, 0x00, 0x00, 0x03, 0x02, 0xda, 0x03, 0x18, 0x03, 0x18,
+        ];
+        let ours = vec![
+            0x2e, 0x03, 0x00, 0xff, 0x00, 0x03, 0x02, 0xda, 0x03, 0x18, 0x03, 0x18,
+        ];
+        let theirs = vec![0x2e, 0x03, 0x00, 0x00, 0x03, 0x02, 0xda, 0x03, 0x18];
+        let (merged, conflicts) = BinaryDiff::merge3(
+            &base,
+            &binary_diff_wrapper(&base, &ours).unwrap(),
+            &binary_diff_wrapper(&base, &theirs).unwrap(),
+            ConflictResolution::Fail,
+        )
+        .unwrap();
+        assert!(conflicts.is_empty());
+        assert_eq!(
+            merged.apply_slice(&base).unwrap(),
+            vec![0x2e, 0x03, 0x00, 0xff, 0x00, 0x03, 0x02, 0xda, 0x03, 0x18]
+        );
+    }
+
+    #[test]
+    fn test_new_with_options_myers() {
+        init();
+
+        let old = std::fs::read(
+            "tests/samples/binary/crash-minimization/crash-235641cefe524570bf0df6a3b3722535ce2dbbf7",
+        )
+        .unwrap();
+        let new = std::fs::read("tests/samples/binary/crash-minimization/minimized-from-10dad543216eabe6d97b9d0ba8459215f6dca3f3")
+            .unwrap();
+        let options = DiffOptions::new().engine(DiffEngine::Myers);
+        let diff = BinaryDiff::new_with_options(
+            &mut BufReader::new(Cursor::new(&old)),
+            &mut BufReader::new(Cursor::new(&new)),
+            &options,
+        )
+        .unwrap();
+        log::trace!("[*] diff() = {:?}", diff);
+        assert_eq!(diff.apply_slice(&old).unwrap(), new);
+
+        // Less Insert() than greedy engine
+        let count_inserted_bytes = |diff: &BinaryDiff| -> usize {
+            diff.chunks()
+                .iter()
+                .map(|chunk| match chunk {
+                    Insert(_, bytes) => bytes.len(),
+                    _ => 0,
+                })
+                .sum()
+        };
+        assert_eq!(count_inserted_bytes(&diff), 1);
+        assert!(count_inserted_bytes(&binary_diff_wrapper(&old, &new).unwrap()) > 1);
+    }
+
+    #[test]
+    fn test_new_with_options_myers_fallback() {
+        init();
+
+        let old = vec![0, 1, 4];
+        let new = vec![2, 3, 4];
+        let options = DiffOptions::new()
+            .engine(DiffEngine::Myers)
+            .myers_max_size(2);
+        let diff = BinaryDiff::new_with_options(
+            &mut BufReader::new(Cursor::new(&old)),
+            &mut BufReader::new(Cursor::new(&new)),
+            &options,
+        )
+        .unwrap();
+        assert_eq!(diff, binary_diff_wrapper(&old, &new).unwrap());
+    }
+
+    #[test]
+    fn test_new_with_options_suffix_array() {
+        init();
+
+        // Inserted bytes are longer than windows of greedy engine
+        let old: Vec<u8> = (0..0x400usize).map(|i| (i * 7 % 251) as u8).collect();
+        let inserted: Vec<u8> = (0..0x80u8).map(|i| i.wrapping_mul(13) ^ 0x5a).collect();
+        let new = [&old[0..0x100], &inserted, &old[0x100..]].concat();
+        let options = DiffOptions::new().engine(DiffEngine::SuffixArray);
+        let diff = BinaryDiff::new_with_options(
+            &mut BufReader::new(Cursor::new(&old)),
+            &mut BufReader::new(Cursor::new(&new)),
+            &options,
+        )
+        .unwrap();
+        log::trace!("[*] diff() = {:?}", diff);
+        assert_eq!(diff.apply_slice(&old).unwrap(), new);
+        assert_eq!(
+            diff.chunks(),
+            &vec![Same(0, 0x100), Insert(0x100, inserted), Same(0x100, 0x300)]
+        );
+    }
+
+    #[test]
+    fn test_new_with_options_rolling_hash() {
+        init();
+
+        // NOTE: Blocks must not repeat in `old`
+        let old: Vec<u8> = (0..0x1000u32)
+            .map(|i| (i.wrapping_mul(2654435761) >> 13) as u8)
+            .collect();
+        let mut new = old.clone();
+        new[0x234] = !new[0x234];
+        let options = DiffOptions::new()
+            .engine(DiffEngine::RollingHash)
+            .rolling_hash_block_size(0x100);
+        let diff = BinaryDiff::new_with_options(
+            &mut BufReader::new(Cursor::new(&old)),
+            &mut BufReader::new(Cursor::new(&new)),
+            &options,
+        )
+        .unwrap();
+        log::trace!("[*] diff() = {:?}", diff);
+        assert_eq!(diff.apply_slice(&old).unwrap(), new);
+        assert_eq!(
+            diff.chunks(),
+            &vec![
+                Same(0, 0x234),
+                Delete(0x234, 1),
+                Insert(0x235, vec![!old[0x234]]),
+                Same(0x235, 0xdcb)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_from_slices_equals_to_new() {
+        init();
+
+        let mut samples = vec![
+            (
+                std::fs::read("tests/samples/binary/seeds/573a46286deaf9df81fb90d7b786708d845b5f23")
+                    .unwrap(),
+                std::fs::read("tests/samples/binary/seeds/c298122410da09836c59484e995c287294c31394")
+                    .unwrap(),
+            ),
+            (
+                std::fs::read("tests/samples/binary/crash-minimization/crash-235641cefe524570bf0df6a3b3722535ce2dbbf7")
+                    .unwrap(),
+                std::fs::read("tests/samples/binary/crash-minimization/minimized-from-10dad543216eabe6d97b9d0ba8459215f6dca3f3")
+                    .unwrap(),
+            ),
+            (vec![], vec![]),
+            (vec![], vec![0, 1]),
+            (vec![0, 1], vec![]),
+        ];
+        let mut seed = 0x1234_5678;
+        for i in 0..50 {
+            let old = random_bytes(&mut seed, i % 37);
+            let mut new = old.clone();
+            let start = std::cmp::min(i % 5, old.len());
+            let end = std::cmp::min(start + i % 3, old.len());
+            new.splice(start..end, random_bytes(&mut seed, i % 11));
+            samples.push((old, new));
+        }
+        for (old, new) in samples.iter() {
+            assert_eq!(
+                BinaryDiff::from_slices(old, new).unwrap(),
+                binary_diff_wrapper(old, new).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_new_distinct_readers() {
+        init();
+
+        let old = vec![0, 1, 2, 3, 4, 5, 6, 7];
+        let new = vec![0, 1, 2, 0xff, 4, 5, 6, 7, 8];
+        let expected = BinaryDiff::from_slices(&old, &new).unwrap();
+        // Cursor<Vec<u8>> and Cursor<&[u8]> are different types of readers
+        let diff = BinaryDiff::new(
+            &mut BufReader::new(Cursor::new(old.clone())),
+            &mut BufReader::new(Cursor::new(new.as_slice())),
+        )
+        .unwrap();
+        assert_eq!(diff, expected);
+        assert_eq!(diff.apply_slice(&old).unwrap(), new);
+    }
+
+    #[test]
+    fn test_new_streaming() {
+        init();
+
+        let old: Vec<u8> = (0..0x1000u32)
+            .map(|i| (i.wrapping_mul(2654435761) >> 13) as u8)
+            .collect();
+        let mut new = old.clone();
+        new[0x234] = !new[0x234];
+        new.drain(0x800..0x810);
+        // NOTE: &[u8] implements Read but does not implement Seek
+        let diff = BinaryDiff::new_streaming(
+            old.as_slice(),
+            new.as_slice(),
+            &DiffOptions::new().streaming_lookahead(0x100),
+        )
+        .unwrap();
+        log::trace!("[*] diff() = {:?}", diff);
+        assert_eq!(diff.apply_slice(&old).unwrap(), new);
+        assert_eq!(
+            diff.chunks(),
+            &vec![
+                Same(0, 0x234),
+                Delete(0x234, 1),
+                Insert(0x235, vec![!old[0x234]]),
+                Same(0x235, 0x5cb),
+                Delete(0x800, 0x10),
+                Same(0x810, 0x7f0)
+            ]
+        );
+
+        let options = DiffOptions::new()
+            .engine(DiffEngine::Streaming)
+            .streaming_lookahead(0x100);
+        assert_eq!(
+            BinaryDiff::new_with_options(
+                &mut BufReader::new(Cursor::new(&old)),
+                &mut BufReader::new(Cursor::new(&new)),
+                &options,
+            )
+            .unwrap(),
+            diff
+        );
+    }
+
+    #[test]
+    fn test_new_with_options_tie_breaking() {
+        init();
+
+        // Algorithm (2) of get_delete_chunk() returned None when nothing was to be deleted before the
+        // next Same() chunk, and diff failed with InfiniteLoopError(0, 0)
+        let old = vec![1, 2, 3];
+        let new = vec![2, 1, 3];
+        let diff_with = |tie_breaking| {
+            let options = DiffOptions::new()
+                .delete_windows(&[2])
+                .tie_breaking(tie_breaking);
+            BinaryDiff::new_with_options(
+                &mut BufReader::new(Cursor::new(&old)),
+                &mut BufReader::new(Cursor::new(&new)),
+                &options,
+            )
+            .unwrap()
+        };
+
+        let diff = diff_with(TieBreaking::PreferDelete);
+        log::trace!("[*] diff() = {:?}", diff);
+        assert_eq!(diff.chunks()[0], Delete(0, 1));
+        assert_eq!(diff.apply_slice(&old).unwrap(), new);
+
+        let diff = diff_with(TieBreaking::PreferInsert);
+        log::trace!("[*] diff() = {:?}", diff);
+        assert_eq!(diff.chunks()[0], Insert(0, vec![2]));
+        assert_eq!(diff.apply_slice(&old).unwrap(), new);
+    }
+
+    #[test]
+    fn test_new_with_options_tie_breaking_default_windows() {
+        init();
+
+        let old = vec![0, 1, 2, 3, 4, 5];
+        let new = vec![1, 2, 3, 4, 5, 0, 1, 2, 3, 4, 5];
+        let diff_with = |old: &[u8], new: &[u8], tie_breaking| {
+            BinaryDiff::new_with_options(
+                &mut BufReader::new(Cursor::new(old)),
+                &mut BufReader::new(Cursor::new(new)),
+                &DiffOptions::new().tie_breaking(tie_breaking),
+            )
+            .unwrap()
+        };
+
+        let diff = diff_with(&old, &new, TieBreaking::PreferDelete);
+        log::trace!("[*] diff() = {:?}", diff);
+        assert_eq!(diff.chunks()[0], Delete(0, 1));
+        assert_eq!(diff.apply_slice(&old).unwrap(), new);
+
+        let diff = diff_with(&old, &new, TieBreaking::PreferInsert);
+        log::trace!("[*] diff() = {:?}", diff);
+        assert_eq!(diff.chunks()[0], Insert(0, vec![1, 2, 3, 4, 5]));
+        assert_eq!(diff.apply_slice(&old).unwrap(), new);
+
+        let old = std::fs::read(
+            "tests/samples/binary/crash-minimization/crash-235641cefe524570bf0df6a3b3722535ce2dbbf7",
+        )
+        .unwrap();
+        let new = std::fs::read("tests/samples/binary/crash-minimization/minimized-from-10dad543216eabe6d97b9d0ba8459215f6dca3f3")
+            .unwrap();
+        let preferring_delete = diff_with(&old, &new, TieBreaking::PreferDelete);
+        let preferring_insert = diff_with(&old, &new, TieBreaking::PreferInsert);
+        assert_ne!(preferring_delete, preferring_insert);
+        assert_eq!(preferring_insert.apply_slice(&old).unwrap(), new);
+    }
+
+    #[test]
+    fn test_new_near_eof() {
+        init();
+
+        // Default hash of 2 bytes finds no Same() chunk in the last byte of `old`
+        let old = vec![0xc0, 0xc9, 0x99];
+        let new = vec![
+            0xc9, 0xe8, 0xbb, 0x10, 0xe3, 0x29, 0x97, 0xc9, 0xb2, 0xd3, 0x6d, 0x12, 0x07, 0x0b,
+            0x28, 0x4e, 0xec, 0x84, 0x99,
+        ];
+        let diff = binary_diff_wrapper(&old, &new).unwrap();
+        log::trace!("[*] diff() = {:?}", diff);
+        assert_eq!(
+            diff.chunks(),
+            &vec![
+                Delete(0, 1),
+                Same(1, 1),
+                Delete(2, 1),
+                Insert(3, new[1..].to_vec())
+            ]
+        );
+        assert_eq!(diff.apply_slice(&old).unwrap(), new);
+
+        // Given hash granularity is shrunk to the last byte
+        let diff = BinaryDiff::new_with_options(
+            &mut BufReader::new(Cursor::new(&old)),
+            &mut BufReader::new(Cursor::new(&new)),
+            &DiffOptions::new().hash_granularity(2),
+        )
+        .unwrap();
+        log::trace!("[*] diff() = {:?}", diff);
+        assert_eq!(diff.chunks().last(), Some(&Same(2, 1)));
+        assert_eq!(diff.apply_slice(&old).unwrap(), new);
+    }
+
+    #[test]
+    fn test_new_with_options_windows() {
+        init();
+
+        // get_insert_chunk() did not seek back when the first byte of `new` equals to `old`, and
+        // patched binary lost the byte
+        let old = std::fs::read(
+            "tests/samples/binary/crash-minimization/crash-235641cefe524570bf0df6a3b3722535ce2dbbf7",
+        )
+        .unwrap();
+        let new = std::fs::read("tests/samples/binary/crash-minimization/minimized-from-10dad543216eabe6d97b9d0ba8459215f6dca3f3")
+            .unwrap();
+        let options = DiffOptions::new()
+            .delete_windows(&[4, 8])
+            .insert_windows(&[2, 4])
+            .hash_granularity(2)
+            .min_match_length(2)
+            .max_lookahead(6);
+        let diff = BinaryDiff::new_with_options(
+            &mut BufReader::new(Cursor::new(&old)),
+            &mut BufReader::new(Cursor::new(&new)),
+            &options,
+        )
+        .unwrap();
+        log::trace!("[*] diff() = {:?}", diff);
+        assert_eq!(diff.apply_slice(&old).unwrap(), new);
+        assert_ne!(diff, binary_diff_wrapper(&old, &new).unwrap());
+    }
+
+    struct ReplaceAllAlgorithm;
+
+    impl DiffAlgorithm for ReplaceAllAlgorithm {
+        fn diff_slices(&self, old: &[u8], new: &[u8]) -> Result<Vec<BinaryDiffChunk>> {
+            Ok(vec![Replace(0, old.len(), new.to_vec())])
+        }
+    }
+
+    #[test]
+    fn test_with_algorithm() {
+        init();
+
+        let old = vec![0, 1, 4];
+        let new = vec![2, 3, 4];
+        let diff = BinaryDiff::with_algorithm(
+            &mut BufReader::new(Cursor::new(&old)),
+            &mut BufReader::new(Cursor::new(&new)),
+            &ReplaceAllAlgorithm,
+        )
+        .unwrap();
+        assert_eq!(diff, BinaryDiff::from(&vec![Replace(0, 3, new.clone())]));
+        assert_eq!(diff.apply_slice(&old).unwrap(), new);
+
+        assert_eq!(
+            GreedyAlgorithm::default().diff_slices(&old, &new).unwrap(),
+            binary_diff_wrapper(&old, &new).unwrap().chunks
+        );
+    }
+
+    #[test]
+    fn test_dyn_diff_algorithm() {
+        init();
+
+        let old = vec![0, 1, 4];
+        let new = vec![2, 3, 4];
+        let algorithms: Vec<Box<dyn DiffAlgorithm>> = vec![
+            Box::new(GreedyAlgorithm::default()),
+            Box::new(ReplaceAllAlgorithm),
+        ];
+        for algorithm in algorithms.iter() {
+            let diff = BinaryDiff::from(&algorithm.diff_slices(&old, &new).unwrap());
+            assert_eq!(diff.apply_slice(&old).unwrap(), new);
+        }
+    }
+
+    #[test]
+    fn test_apply_inconsistent_diff() {
+        let diff = BinaryDiff::from(&vec![Same(0, 4)]);
+        assert!(diff.apply_slice(&[0, 1, 2]).is_err());
+        assert!(diff.apply_slice(&[0, 1, 2, 3, 4]).is_err());
+    }
+
+    #[test]
+    fn test_apply_realworld_sample() {
+        let original = vec![
+            0x7B, 0x31, 0x12, 0x00, 0x00, 0x01, 0x03, 0x00, 0x01, 0x00, 0x00, 0x00, 0xB6, 0x00,
+            0x8C, 0xC9, 0x01, 0x01, 0x03, 0x00, 0x01, 0x00, 0x00, 0x00, 0x20, 0x00, 0xBB, 0x00,
+            0x02, 0x01, 0x03, 0x00, 0x01, 0x00, 0x00, 0x00, 0x04, 0x00, 0x47, 0x00, 0x03, 0x01,
+            0x03, 0x00, 0x01, 0x00, 0x00, 0x00, 0x75, 0x87, 0x00, 0x00, 0x06, 0x01, 0x03, 0x00,
+            0x01, 0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0xAD, 0x0A, 0x01, 0x03, 0x00, 0x01, 0x00,
+            0x00, 0x00, 0x01, 0x00, 0x00, 0x58, 0x0D, 0x01, 0x02, 0x00, 0x0F, 0x00, 0x00, 0x00,
+            0x40, 0x01, 0x00, 0x00, 0x11, 0x01, 0x04, 0x00, 0x01, 0x00, 0x04, 0x9D,
+        ];
+        let edited = vec![
+            0xFE, 0x00, 0x12, 0x00, 0x00, 0x01, 0x03, 0x00, 0x01, 0x00, 0x00, 0x00, 0x20, 0x00,
+            0xA5, 0xC3, 0x01, 0x01, 0x03, 0x00, 0x01, 0x00, 0x00, 0x00, 0x20, 0x00, 0xA6, 0x00,
+            0x02, 0x01, 0x03, 0x00, 0x01, 0x00, 0x00, 0x00, 0x04, 0x00, 0x68, 0x00, 0x03, 0x01,
+            0x03, 0x00, 0x01, 0x00, 0x00, 0x00, 0x08, 0x00, 0x00, 0x00, 0x06, 0x01, 0x03, 0x00,
+            0x01, 0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x63, 0x0A, 0x01, 0x03, 0x00, 0x01, 0x00,
+            0x00, 0x00, 0x01, 0x00, 0x00, 0xB0, 0x0D, 0x01, 0x02, 0x00, 0x0F, 0x00, 0x00, 0x00,
+            0x40, 0x01, 0x00, 0x00, 0x11, 0x01, 0x04, 0x00, 0x01, 0x00, 0x31, 0x00,
+        ];
+        let diff = binary_diff_wrapper(&original, &edited).unwrap();
+        assert_eq!(diff.apply_slice(&original).unwrap(), edited);
+    }
+
     #[test]
     fn realworld_sample() {
         let original = vec![
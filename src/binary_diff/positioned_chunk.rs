@@ -0,0 +1,57 @@
+use crate::binary_diff::binary_diff_chunk::BinaryDiffChunk;
+use std::ops::Range;
+
+// Chunk with ranges that it occupies in original binary and in patched binary
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PositionedChunk<'a> {
+    pub(crate) chunk: &'a BinaryDiffChunk,
+    pub(crate) patched_offset: usize,
+}
+
+impl<'a> PositionedChunk<'a> {
+    pub fn chunk(&self) -> &'a BinaryDiffChunk {
+        self.chunk
+    }
+
+    // Range of original binary consumed by the chunk. Empty for Insert() and Copy() chunks
+    pub fn original_range(&self) -> Range<usize> {
+        *self.chunk.offset()..self.chunk.offset() + self.chunk.original_length()
+    }
+
+    // Range of patched binary introduced by the chunk. Empty for Delete() chunk
+    pub fn patched_range(&self) -> Range<usize> {
+        self.patched_offset..self.patched_offset + self.chunk.patched_length()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BinaryDiff, BinaryDiffChunk};
+
+    #[test]
+    fn test_iter_with_positions() {
+        let diff = BinaryDiff::from(&vec![
+            BinaryDiffChunk::Same(0, 2),
+            BinaryDiffChunk::Delete(2, 3),
+            BinaryDiffChunk::Insert(5, vec![0, 1]),
+            BinaryDiffChunk::Same(5, 1),
+            BinaryDiffChunk::Replace(6, 2, vec![2, 3, 4]),
+            BinaryDiffChunk::Copy(8, 0, 2),
+        ]);
+        let positions: Vec<_> = diff
+            .iter_with_positions()
+            .map(|positioned| (positioned.original_range(), positioned.patched_range()))
+            .collect();
+        assert_eq!(
+            positions,
+            vec![
+                (0..2, 0..2),
+                (2..5, 2..2),
+                (5..5, 2..4),
+                (5..6, 4..5),
+                (6..8, 5..8),
+                (8..8, 8..10)
+            ]
+        );
+    }
+}
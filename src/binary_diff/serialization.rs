@@ -0,0 +1,244 @@
+use super::binary_diff_chunk::BinaryDiffChunk;
+use super::BinaryDiff;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+// Schema of BinaryDiff: {"chunks": [...]}
+#[derive(Serialize)]
+#[serde(rename = "BinaryDiff")]
+struct SerializedBinaryDiff<'a> {
+    chunks: &'a [BinaryDiffChunk],
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "BinaryDiff")]
+struct DeserializedBinaryDiff {
+    chunks: Vec<BinaryDiffChunk>,
+}
+
+// Schema of BinaryDiffChunk in human-readable formats such as JSON. Bytes are lowercase hex string:
+//   {"kind": "Same", "offset": 0, "length": 4}
+//   {"kind": "Insert", "offset": 4, "bytes": "5c16"}
+//   {"kind": "Delete", "offset": 4, "length": 2}
+//   {"kind": "Replace", "offset": 4, "length": 2, "bytes": "5c16"}
+//   {"kind": "Copy", "offset": 4, "source": 0, "length": 2}
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "kind")]
+enum HumanReadableChunk {
+    Same {
+        offset: usize,
+        length: usize,
+    },
+    Insert {
+        offset: usize,
+        #[serde(with = "hex")]
+        bytes: Vec<u8>,
+    },
+    Delete {
+        offset: usize,
+        length: usize,
+    },
+    Replace {
+        offset: usize,
+        length: usize,
+        #[serde(with = "hex")]
+        bytes: Vec<u8>,
+    },
+    Copy {
+        offset: usize,
+        source: usize,
+        length: usize,
+    },
+}
+
+// Schema of BinaryDiffChunk in compact binary formats such as bincode. Same as variants of BinaryDiffChunk
+#[derive(Serialize, Deserialize)]
+enum CompactChunk {
+    Same(usize, usize),
+    Insert(usize, Vec<u8>),
+    Delete(usize, usize),
+    Replace(usize, usize, Vec<u8>),
+    Copy(usize, usize, usize),
+}
+
+impl From<&BinaryDiffChunk> for HumanReadableChunk {
+    fn from(chunk: &BinaryDiffChunk) -> Self {
+        match chunk.clone() {
+            BinaryDiffChunk::Same(offset, length) => Self::Same { offset, length },
+            BinaryDiffChunk::Insert(offset, bytes) => Self::Insert { offset, bytes },
+            BinaryDiffChunk::Delete(offset, length) => Self::Delete { offset, length },
+            BinaryDiffChunk::Replace(offset, length, bytes) => Self::Replace {
+                offset,
+                length,
+                bytes,
+            },
+            BinaryDiffChunk::Copy(offset, source, length) => Self::Copy {
+                offset,
+                source,
+                length,
+            },
+        }
+    }
+}
+
+impl From<HumanReadableChunk> for BinaryDiffChunk {
+    fn from(chunk: HumanReadableChunk) -> Self {
+        match chunk {
+            HumanReadableChunk::Same { offset, length } => Self::Same(offset, length),
+            HumanReadableChunk::Insert { offset, bytes } => Self::Insert(offset, bytes),
+            HumanReadableChunk::Delete { offset, length } => Self::Delete(offset, length),
+            HumanReadableChunk::Replace {
+                offset,
+                length,
+                bytes,
+            } => Self::Replace(offset, length, bytes),
+            HumanReadableChunk::Copy {
+                offset,
+                source,
+                length,
+            } => Self::Copy(offset, source, length),
+        }
+    }
+}
+
+impl From<&BinaryDiffChunk> for CompactChunk {
+    fn from(chunk: &BinaryDiffChunk) -> Self {
+        match chunk.clone() {
+            BinaryDiffChunk::Same(offset, length) => Self::Same(offset, length),
+            BinaryDiffChunk::Insert(offset, bytes) => Self::Insert(offset, bytes),
+            BinaryDiffChunk::Delete(offset, length) => Self::Delete(offset, length),
+            BinaryDiffChunk::Replace(offset, length, bytes) => Self::Replace(offset, length, bytes),
+            BinaryDiffChunk::Copy(offset, source, length) => Self::Copy(offset, source, length),
+        }
+    }
+}
+
+impl From<CompactChunk> for BinaryDiffChunk {
+    fn from(chunk: CompactChunk) -> Self {
+        match chunk {
+            CompactChunk::Same(offset, length) => Self::Same(offset, length),
+            CompactChunk::Insert(offset, bytes) => Self::Insert(offset, bytes),
+            CompactChunk::Delete(offset, length) => Self::Delete(offset, length),
+            CompactChunk::Replace(offset, length, bytes) => Self::Replace(offset, length, bytes),
+            CompactChunk::Copy(offset, source, length) => Self::Copy(offset, source, length),
+        }
+    }
+}
+
+impl Serialize for BinaryDiffChunk {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            HumanReadableChunk::from(self).serialize(serializer)
+        } else {
+            CompactChunk::from(self).serialize(serializer)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for BinaryDiffChunk {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            HumanReadableChunk::deserialize(deserializer).map(Self::from)
+        } else {
+            CompactChunk::deserialize(deserializer).map(Self::from)
+        }
+    }
+}
+
+impl Serialize for BinaryDiff {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        SerializedBinaryDiff {
+            chunks: &self.chunks,
+        }
+        .serialize(serializer)
+    }
+}
+
+// NOTE: Positions of chunks are not serialized but calculated again
+impl<'de> Deserialize<'de> for BinaryDiff {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        DeserializedBinaryDiff::deserialize(deserializer).map(|diff| Self::with_chunks(diff.chunks))
+    }
+}
+
+mod hex {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        let hex: String = bytes.iter().map(|byte| format!("{:02x}", byte)).collect();
+        serializer.serialize_str(&hex)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let hex = String::deserialize(deserializer)?;
+        if hex.len() % 2 != 0 {
+            return Err(D::Error::custom("hex string has odd length"));
+        }
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| {
+                hex.get(i..i + 2)
+                    .and_then(|digits| u8::from_str_radix(digits, 16).ok())
+                    .ok_or_else(|| D::Error::custom(format!("invalid hex string: {}", hex)))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::binary_diff::binary_diff_chunk::BinaryDiffChunk::{
+        Copy, Delete, Insert, Replace, Same,
+    };
+    use crate::BinaryDiff;
+
+    fn sample() -> BinaryDiff {
+        BinaryDiff::from(&vec![
+            Same(0, 4),
+            Delete(4, 2),
+            Insert(6, vec![0x5c, 0x16]),
+            Same(6, 2),
+            Replace(8, 2, vec![0xff]),
+            Copy(10, 0, 2),
+        ])
+    }
+
+    #[test]
+    fn test_serialize_json() {
+        let diff = sample();
+        let json = serde_json::to_string(&diff).unwrap();
+        assert_eq!(
+            json,
+            concat!(
+                r#"{"chunks":["#,
+                r#"{"kind":"Same","offset":0,"length":4},"#,
+                r#"{"kind":"Delete","offset":4,"length":2},"#,
+                r#"{"kind":"Insert","offset":6,"bytes":"5c16"},"#,
+                r#"{"kind":"Same","offset":6,"length":2},"#,
+                r#"{"kind":"Replace","offset":8,"length":2,"bytes":"ff"},"#,
+                r#"{"kind":"Copy","offset":10,"source":0,"length":2}"#,
+                r#"]}"#
+            )
+        );
+        assert_eq!(serde_json::from_str::<BinaryDiff>(&json).unwrap(), diff);
+    }
+
+    #[test]
+    fn test_deserialize_json_invalid_hex() {
+        for bytes in ["5", "5g", "é"].iter() {
+            let json = format!(
+                r#"{{"chunks":[{{"kind":"Insert","offset":0,"bytes":"{}"}}]}}"#,
+                bytes
+            );
+            assert!(serde_json::from_str::<BinaryDiff>(&json).is_err());
+        }
+    }
+
+    #[test]
+    fn test_serialize_bincode() {
+        let diff = sample();
+        let encoded = bincode::serialize(&diff).unwrap();
+        assert!(encoded.len() < serde_json::to_vec(&diff).unwrap().len());
+        assert_eq!(bincode::deserialize::<BinaryDiff>(&encoded).unwrap(), diff);
+    }
+}
@@ -1,4 +1,5 @@
 use crate::BinaryDiffChunk;
+use std::ops::Range;
 
 #[derive(Debug, Eq, PartialEq)]
 pub struct DerivesFrom<'a> {
@@ -14,6 +15,10 @@ impl<'a> DerivesFrom<'a> {
                 debug_assert!(&self.relative_position < length);
                 Some(original_offset + self.relative_position)
             }
+            BinaryDiffChunk::Copy(_, source, length) => {
+                debug_assert!(&self.relative_position < length);
+                Some(source + self.relative_position)
+            }
             _ => None,
         }
     }
@@ -31,9 +36,42 @@ impl<'a> DerivesFrom<'a> {
     }
 }
 
+// Contiguous bytes of patched binary that derive from the same chunk, or from no chunks
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct DerivesFromRange<'a> {
+    pub(crate) patched_range: Range<usize>,
+    pub(crate) relative_position: usize,
+    pub(crate) chunk: Option<&'a BinaryDiffChunk>,
+}
+
+impl<'a> DerivesFromRange<'a> {
+    // Returns range of original binary that bytes are taken from. Only Same() and Copy() chunks have it
+    pub fn original_range(&self) -> Option<Range<usize>> {
+        let start = match self.chunk? {
+            BinaryDiffChunk::Same(original_offset, _) => original_offset + self.relative_position,
+            BinaryDiffChunk::Copy(_, source, _) => source + self.relative_position,
+            _ => return None,
+        };
+        Some(start..start + self.patched_range.len())
+    }
+
+    pub fn patched_range(&self) -> Range<usize> {
+        self.patched_range.clone()
+    }
+
+    // Position of the first byte relative to the chunk
+    pub fn relative_position(&self) -> usize {
+        self.relative_position
+    }
+
+    pub fn chunk(&self) -> Option<&'a BinaryDiffChunk> {
+        self.chunk
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::{BinaryDiffChunk, DerivesFrom};
+    use crate::{BinaryDiffChunk, DerivesFrom, DerivesFromRange};
 
     #[test]
     fn test_derives_from_same() {
@@ -46,6 +84,17 @@ mod tests {
         assert_eq!(derives_from_same.original_position(), Some(2));
     }
 
+    #[test]
+    fn test_derives_from_copy() {
+        let chunk = BinaryDiffChunk::Copy(3, 8, 4);
+        let derives_from = DerivesFrom {
+            patched_position: 5,
+            relative_position: 2,
+            chunk: &chunk,
+        };
+        assert_eq!(derives_from.original_position(), Some(10));
+    }
+
     #[test]
     fn test_derives_from_insert() {
         let chunk = BinaryDiffChunk::Insert(3, vec![0]);
@@ -56,4 +105,21 @@ mod tests {
         };
         assert_eq!(derives_from.original_position(), None);
     }
+
+    #[test]
+    fn test_derives_from_range_original_range() {
+        let chunk = BinaryDiffChunk::Copy(3, 8, 4);
+        let derives_from = DerivesFromRange {
+            patched_range: 5..7,
+            relative_position: 1,
+            chunk: Some(&chunk),
+        };
+        assert_eq!(derives_from.original_range(), Some(9..11));
+        let derives_from = DerivesFromRange {
+            patched_range: 5..7,
+            relative_position: 1,
+            chunk: None,
+        };
+        assert_eq!(derives_from.original_range(), None);
+    }
 }
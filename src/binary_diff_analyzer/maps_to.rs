@@ -0,0 +1,38 @@
+use crate::{BinaryDiffChunk, PositionedChunk};
+
+// Where a byte of original binary goes in patched binary
+#[derive(Debug, Eq, PartialEq)]
+pub struct MapsTo<'a> {
+    pub(crate) original_position: usize,
+    pub(crate) patched_positions: Vec<usize>,
+    // Same(), Delete() or Replace() chunk that consumes the byte
+    pub(crate) chunk: PositionedChunk<'a>,
+}
+
+impl<'a> MapsTo<'a> {
+    pub fn original_position(&self) -> usize {
+        self.original_position
+    }
+
+    // Offsets of patched binary where the byte survives, in ascending order.
+    // Bytes taken by Copy() chunks survive even if they are removed.
+    pub fn patched_positions(&self) -> &[usize] {
+        &self.patched_positions
+    }
+
+    // Returns Delete() or Replace() chunk if the byte is removed
+    pub fn removed_by(&self) -> Option<&'a BinaryDiffChunk> {
+        match self.chunk.chunk() {
+            BinaryDiffChunk::Same(_, _) => None,
+            chunk => Some(chunk),
+        }
+    }
+
+    pub fn relative_position(&self) -> usize {
+        self.original_position - self.chunk.original_range().start
+    }
+
+    pub fn chunk(&self) -> &PositionedChunk<'a> {
+        &self.chunk
+    }
+}
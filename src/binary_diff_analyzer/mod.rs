@@ -1,16 +1,26 @@
 mod error;
+mod original_index;
 mod result;
 
 pub(super) mod derives_from;
+pub(super) mod maps_to;
+pub(super) mod patched_index;
+pub(super) mod provenance;
 
 use crate::{BinaryDiff, BinaryDiffChunk};
-use derives_from::DerivesFrom;
+use derives_from::{DerivesFrom, DerivesFromRange};
 use error::BinaryDiffAnalyzerError;
+use maps_to::MapsTo;
+use original_index::OriginalIndex;
+use patched_index::PatchedIndex;
+use provenance::{merge_provenance_runs, ProvenanceRun};
 use result::Result;
 use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::ops::Range;
 
 pub struct BinaryDiffAnalyzer<'a, R: Read + Seek> {
-    diff: &'a BinaryDiff,
+    index: PatchedIndex<'a>,
+    original_index: OriginalIndex<'a>,
     patched: BufReader<R>,
 }
 
@@ -20,7 +30,8 @@ where
 {
     pub fn new(diff: &'a BinaryDiff, patched: R) -> Self {
         Self {
-            diff,
+            index: PatchedIndex::new(diff),
+            original_index: OriginalIndex::new(diff),
             patched: BufReader::new(patched),
         }
     }
@@ -37,48 +48,181 @@ where
             value[0]
         };
 
-        Ok(_derives_from(&self.diff, offset, value))
+        Ok(_derives_from(&self.index, offset, value))
+    }
+
+    // Splits `range` of patched binary into runs of bytes that derive from the same chunk.
+    // Bytes that derive from no chunks are also reported as runs whose chunk is None.
+    pub fn derives_from_range(&mut self, range: Range<usize>) -> Result<Vec<DerivesFromRange<'a>>> {
+        let values = {
+            self.patched
+                .seek(SeekFrom::Start(range.start as u64))
+                .map_err(BinaryDiffAnalyzerError::IoError)?;
+            let mut values = vec![0u8; range.len()];
+            self.patched
+                .read_exact(&mut values)
+                .map_err(BinaryDiffAnalyzerError::IoError)?;
+            values
+        };
+
+        Ok(_derives_from_range(&self.index, range, &values))
+    }
+
+    // Returns run-length encoded provenance of each byte of whole patched binary
+    pub fn provenance(&mut self) -> Result<Vec<ProvenanceRun>> {
+        let length = self
+            .patched
+            .seek(SeekFrom::End(0))
+            .map_err(BinaryDiffAnalyzerError::IoError)? as usize;
+        let runs = self.derives_from_range(0..length)?;
+        Ok(merge_provenance_runs(
+            runs.iter().map(ProvenanceRun::from).collect(),
+        ))
+    }
+
+    // Returns None if `offset` is out of original binary
+    pub fn maps_to(&self, offset: usize) -> Option<MapsTo<'a>> {
+        _maps_to(&self.original_index, offset)
     }
 }
 
-fn _derives_from(diff: &BinaryDiff, new_offset: usize, value: u8) -> Option<DerivesFrom> {
-    let mut applied_new_offset = 0usize;
-    for chunk in diff.chunks().iter() {
-        if (applied_new_offset..(applied_new_offset + chunk.patched_length())).contains(&new_offset)
-        {
-            match chunk {
-                BinaryDiffChunk::Same(_, _) => {
-                    return Some(DerivesFrom {
-                        patched_position: new_offset,
-                        relative_position: new_offset - applied_new_offset,
-                        chunk,
-                    })
-                }
-                BinaryDiffChunk::Insert(_, bytes) | BinaryDiffChunk::Replace(_, _, bytes) => {
-                    return if value == bytes[new_offset - applied_new_offset] {
-                        Some(DerivesFrom {
-                            patched_position: new_offset,
-                            relative_position: new_offset - applied_new_offset,
-                            chunk,
-                        })
-                    } else {
-                        None
+fn _derives_from<'a>(
+    index: &PatchedIndex<'a>,
+    new_offset: usize,
+    value: u8,
+) -> Option<DerivesFrom<'a>> {
+    let positioned = index.find(new_offset)?;
+    let relative_position = new_offset - positioned.patched_range().start;
+    match positioned.chunk() {
+        BinaryDiffChunk::Same(_, _) | BinaryDiffChunk::Copy(_, _, _) => Some(DerivesFrom {
+            patched_position: new_offset,
+            relative_position,
+            chunk: positioned.chunk(),
+        }),
+        BinaryDiffChunk::Insert(_, bytes) | BinaryDiffChunk::Replace(_, _, bytes) => {
+            if value == bytes[relative_position] {
+                Some(DerivesFrom {
+                    patched_position: new_offset,
+                    relative_position,
+                    chunk: positioned.chunk(),
+                })
+            } else {
+                None
+            }
+        }
+        // NOTE: Delete() chunk does not affect patched files
+        BinaryDiffChunk::Delete(_, _) => None,
+    }
+}
+
+fn _maps_to<'a>(index: &OriginalIndex<'a>, original_offset: usize) -> Option<MapsTo<'a>> {
+    let positioned = index.find(original_offset)?;
+    let mut patched_positions: Vec<usize> = index.copied_to(original_offset).collect();
+    if let BinaryDiffChunk::Same(offset, _) = positioned.chunk() {
+        patched_positions.push(positioned.patched_range().start + original_offset - offset);
+    }
+    patched_positions.sort_unstable();
+    Some(MapsTo {
+        original_position: original_offset,
+        patched_positions,
+        chunk: positioned.clone(),
+    })
+}
+
+fn push_derives_from_range<'a>(runs: &mut Vec<DerivesFromRange<'a>>, run: DerivesFromRange<'a>) {
+    if run.patched_range.is_empty() {
+        return;
+    }
+    match runs.last_mut() {
+        Some(last) if last.chunk.is_none() && run.chunk.is_none() => {
+            last.patched_range.end = run.patched_range.end
+        }
+        _ => runs.push(run),
+    }
+}
+
+// `values` are bytes of patched binary in `range`
+fn _derives_from_range<'a>(
+    index: &PatchedIndex<'a>,
+    range: Range<usize>,
+    values: &[u8],
+) -> Vec<DerivesFromRange<'a>> {
+    let mut runs = vec![];
+    let mut position = range.start;
+    for positioned in index.overlapping(range.clone()).iter() {
+        let patched_range = positioned.patched_range();
+        let overlap = std::cmp::max(range.start, patched_range.start)
+            ..std::cmp::min(range.end, patched_range.end);
+        push_derives_from_range(
+            &mut runs,
+            DerivesFromRange {
+                patched_range: position..overlap.start,
+                relative_position: 0,
+                chunk: None,
+            },
+        );
+
+        match positioned.chunk() {
+            BinaryDiffChunk::Insert(_, bytes) | BinaryDiffChunk::Replace(_, _, bytes) => {
+                // Bytes of patched binary that differ from the chunk do not derive from it
+                let mut start = overlap.start;
+                while start < overlap.end {
+                    let matches = |offset: usize| {
+                        values[offset - range.start] == bytes[offset - patched_range.start]
                     };
+                    let matched = matches(start);
+                    let end = (start..overlap.end)
+                        .find(|offset| matches(*offset) != matched)
+                        .unwrap_or(overlap.end);
+                    push_derives_from_range(
+                        &mut runs,
+                        DerivesFromRange {
+                            patched_range: start..end,
+                            relative_position: start - patched_range.start,
+                            chunk: if matched {
+                                Some(positioned.chunk())
+                            } else {
+                                None
+                            },
+                        },
+                    );
+                    start = end;
                 }
-                // NOTE: Delete() chunk does not affect patched files
-                BinaryDiffChunk::Delete(_, _) => (),
             }
+            _ => push_derives_from_range(
+                &mut runs,
+                DerivesFromRange {
+                    patched_range: overlap.clone(),
+                    relative_position: overlap.start - patched_range.start,
+                    chunk: Some(positioned.chunk()),
+                },
+            ),
         }
-
-        applied_new_offset += chunk.patched_length()
+        position = overlap.end;
     }
-    None
+    push_derives_from_range(
+        &mut runs,
+        DerivesFromRange {
+            patched_range: position..range.end,
+            relative_position: 0,
+            chunk: None,
+        },
+    );
+    runs
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::binary_diff::binary_diff_chunk::BinaryDiffChunk::{Delete, Insert, Replace, Same};
-    use crate::binary_diff_analyzer::{BinaryDiffAnalyzer, DerivesFrom, _derives_from};
+    use crate::binary_diff::binary_diff_chunk::BinaryDiffChunk::{
+        Copy, Delete, Insert, Replace, Same,
+    };
+    use crate::binary_diff_analyzer::original_index::OriginalIndex;
+    use crate::binary_diff_analyzer::patched_index::PatchedIndex;
+    use crate::binary_diff_analyzer::provenance::Provenance;
+    use crate::binary_diff_analyzer::{
+        _derives_from, _derives_from_range, _maps_to, BinaryDiffAnalyzer, DerivesFrom,
+        DerivesFromRange,
+    };
     use crate::BinaryDiff;
     use std::io::Cursor;
 
@@ -97,22 +241,24 @@ mod tests {
                     chunk: &chunk
                 })
             ),
-            Err(why) => assert!(false, "{:?}", why),
+            Err(why) => panic!("{:?}", why),
         }
     }
 
     #[test]
     fn test_derives_from_none() {
         let diff = BinaryDiff::from(&vec![Same(0, 4)]);
-        assert_eq!(_derives_from(&diff, 5, 0), None);
+        let index = PatchedIndex::new(&diff);
+        assert_eq!(_derives_from(&index, 5, 0), None);
     }
 
     #[test]
     fn test_derives_from_delete_same() {
         let chunk = Same(6, 2);
         let diff = BinaryDiff::from(&vec![Same(0, 4), Delete(4, 2), chunk.clone()]);
+        let index = PatchedIndex::new(&diff);
         assert_eq!(
-            _derives_from(&diff, 4, 0),
+            _derives_from(&index, 4, 0),
             Some(DerivesFrom {
                 patched_position: 4,
                 relative_position: 0,
@@ -120,22 +266,23 @@ mod tests {
             })
         );
         assert_eq!(
-            _derives_from(&diff, 5, 0),
+            _derives_from(&index, 5, 0),
             Some(DerivesFrom {
                 patched_position: 5,
                 relative_position: 1,
                 chunk: &chunk
             })
         );
-        assert_eq!(_derives_from(&diff, 6, 0), None);
+        assert_eq!(_derives_from(&index, 6, 0), None);
     }
 
     #[test]
     fn test_derives_from_insert() {
         let chunk = Insert(0, vec![0, 1, 2, 3]);
         let diff = BinaryDiff::from(&vec![chunk.clone()]);
+        let index = PatchedIndex::new(&diff);
         assert_eq!(
-            _derives_from(&diff, 0, 0),
+            _derives_from(&index, 0, 0),
             Some(DerivesFrom {
                 patched_position: 0,
                 relative_position: 0,
@@ -143,7 +290,7 @@ mod tests {
             })
         );
         assert_eq!(
-            _derives_from(&diff, 1, 1),
+            _derives_from(&index, 1, 1),
             Some(DerivesFrom {
                 patched_position: 1,
                 relative_position: 1,
@@ -151,7 +298,7 @@ mod tests {
             })
         );
         assert_eq!(
-            _derives_from(&diff, 2, 2),
+            _derives_from(&index, 2, 2),
             Some(DerivesFrom {
                 patched_position: 2,
                 relative_position: 2,
@@ -159,22 +306,23 @@ mod tests {
             })
         );
         assert_eq!(
-            _derives_from(&diff, 3, 3),
+            _derives_from(&index, 3, 3),
             Some(DerivesFrom {
                 patched_position: 3,
                 relative_position: 3,
                 chunk: &chunk
             })
         );
-        assert_eq!(_derives_from(&diff, 4, 4), None);
+        assert_eq!(_derives_from(&index, 4, 4), None);
     }
 
     #[test]
     fn test_derives_from_replace() {
         let chunk = Replace(4, 2, vec![0, 1, 2, 3]);
         let diff = BinaryDiff::from(&vec![Same(0, 4), chunk.clone()]);
+        let index = PatchedIndex::new(&diff);
         assert_eq!(
-            _derives_from(&diff, 4, 0),
+            _derives_from(&index, 4, 0),
             Some(DerivesFrom {
                 patched_position: 4,
                 relative_position: 0,
@@ -182,7 +330,7 @@ mod tests {
             })
         );
         assert_eq!(
-            _derives_from(&diff, 5, 1),
+            _derives_from(&index, 5, 1),
             Some(DerivesFrom {
                 patched_position: 5,
                 relative_position: 1,
@@ -190,7 +338,7 @@ mod tests {
             })
         );
         assert_eq!(
-            _derives_from(&diff, 6, 2),
+            _derives_from(&index, 6, 2),
             Some(DerivesFrom {
                 patched_position: 6,
                 relative_position: 2,
@@ -198,14 +346,14 @@ mod tests {
             })
         );
         assert_eq!(
-            _derives_from(&diff, 7, 3),
+            _derives_from(&index, 7, 3),
             Some(DerivesFrom {
                 patched_position: 7,
                 relative_position: 3,
                 chunk: &chunk
             })
         );
-        assert_eq!(_derives_from(&diff, 8, 0), None);
+        assert_eq!(_derives_from(&index, 8, 0), None);
     }
 
     #[test]
@@ -216,8 +364,9 @@ mod tests {
             Replace(4, 2, vec![0, 1, 2, 3]),
             chunk.clone(),
         ]);
+        let index = PatchedIndex::new(&diff);
         assert_eq!(
-            _derives_from(&diff, 8, 0),
+            _derives_from(&index, 8, 0),
             Some(DerivesFrom {
                 patched_position: 8,
                 relative_position: 0,
@@ -225,13 +374,154 @@ mod tests {
             })
         );
         assert_eq!(
-            _derives_from(&diff, 9, 0),
+            _derives_from(&index, 9, 0),
             Some(DerivesFrom {
                 patched_position: 9,
                 relative_position: 1,
                 chunk: &chunk
             })
         );
-        assert_eq!(_derives_from(&diff, 10, 0), None);
+        assert_eq!(_derives_from(&index, 10, 0), None);
+    }
+
+    #[test]
+    fn test_derives_from_copy() {
+        let chunk = Copy(4, 0, 2);
+        let diff = BinaryDiff::from(&vec![Same(0, 4), chunk.clone()]);
+        let index = PatchedIndex::new(&diff);
+        assert_eq!(
+            _derives_from(&index, 5, 0),
+            Some(DerivesFrom {
+                patched_position: 5,
+                relative_position: 1,
+                chunk: &chunk
+            })
+        );
+        assert_eq!(_derives_from(&index, 6, 0), None);
+    }
+
+    #[test]
+    fn test_derives_from_range() {
+        let copy = Copy(4, 0, 2);
+        let replace = Replace(6, 2, vec![0, 1, 2, 3]);
+        let same = Same(8, 2);
+        let diff = BinaryDiff::from(&vec![
+            Same(0, 4),
+            copy.clone(),
+            replace.clone(),
+            same.clone(),
+        ]);
+        let index = PatchedIndex::new(&diff);
+        // Patched binary is [0..4] ++ [0..2] ++ [0, 1, 2, 3] ++ [8, 9]
+        let values = [2, 3, 0, 1, 0, 9, 9, 3, 8, 9];
+        assert_eq!(
+            _derives_from_range(&index, 2..12, &values),
+            vec![
+                DerivesFromRange {
+                    patched_range: 2..4,
+                    relative_position: 2,
+                    chunk: Some(&diff.chunks()[0]),
+                },
+                DerivesFromRange {
+                    patched_range: 4..6,
+                    relative_position: 0,
+                    chunk: Some(&copy),
+                },
+                DerivesFromRange {
+                    patched_range: 6..7,
+                    relative_position: 0,
+                    chunk: Some(&replace),
+                },
+                DerivesFromRange {
+                    patched_range: 7..9,
+                    relative_position: 1,
+                    chunk: None,
+                },
+                DerivesFromRange {
+                    patched_range: 9..10,
+                    relative_position: 3,
+                    chunk: Some(&replace),
+                },
+                DerivesFromRange {
+                    patched_range: 10..12,
+                    relative_position: 0,
+                    chunk: Some(&same),
+                },
+            ]
+        );
+        assert_eq!(_derives_from_range(&index, 3..3, &[]), vec![]);
+    }
+
+    #[test]
+    fn test_binary_diff_analyzer_derives_from_range() {
+        let chunk = Insert(0, vec![0, 1, 2, 3]);
+        let diff = BinaryDiff::from(&vec![chunk.clone()]);
+        let buf = Cursor::new(vec![0, 1, 7, 3]);
+        let mut analyzer = BinaryDiffAnalyzer::new(&diff, buf);
+        let runs = analyzer.derives_from_range(1..4).unwrap();
+        assert_eq!(
+            runs.iter()
+                .map(|run| run.patched_range())
+                .collect::<Vec<_>>(),
+            vec![1..2, 2..3, 3..4]
+        );
+        assert_eq!(runs[1].chunk(), None);
+        assert!(analyzer.derives_from_range(2..5).is_err());
+    }
+
+    #[test]
+    fn test_maps_to() {
+        let diff = BinaryDiff::from(&vec![
+            Same(0, 4),
+            Delete(4, 2),
+            Insert(6, vec![0, 1]),
+            Replace(6, 2, vec![2]),
+            Copy(8, 1, 2),
+        ]);
+        let index = OriginalIndex::new(&diff);
+        let maps_to = |offset| {
+            _maps_to(&index, offset).map(|maps_to| {
+                (
+                    maps_to.patched_positions().to_vec(),
+                    maps_to.removed_by().cloned(),
+                )
+            })
+        };
+        // Patched binary is [0..4] ++ [0, 1] ++ [2] ++ [1..3]
+        assert_eq!(maps_to(0), Some((vec![0], None)));
+        assert_eq!(maps_to(2), Some((vec![2, 8], None)));
+        assert_eq!(maps_to(4), Some((vec![], Some(Delete(4, 2)))));
+        assert_eq!(maps_to(7), Some((vec![], Some(Replace(6, 2, vec![2])))));
+        assert_eq!(maps_to(8), None);
+
+        let maps_to = _maps_to(&index, 5).unwrap();
+        assert_eq!(maps_to.original_position(), 5);
+        assert_eq!(maps_to.relative_position(), 1);
+    }
+
+    #[test]
+    fn test_binary_diff_analyzer_provenance() {
+        let diff = BinaryDiff::from(&vec![
+            Same(0, 4),
+            Delete(4, 2),
+            Insert(6, vec![0, 1]),
+            Replace(6, 2, vec![2]),
+            Copy(8, 1, 2),
+        ]);
+        let buf = Cursor::new(vec![0, 1, 2, 3, 0, 1, 2, 1, 2, 9]);
+        let mut analyzer = BinaryDiffAnalyzer::new(&diff, buf);
+        let runs = analyzer.provenance().unwrap();
+        assert_eq!(
+            runs.iter()
+                .map(|run| (run.patched_range(), run.provenance()))
+                .collect::<Vec<_>>(),
+            vec![
+                (0..4, Provenance::Preserved(0)),
+                (4..6, Provenance::Inserted),
+                (6..7, Provenance::Replaced),
+                (7..9, Provenance::Preserved(1)),
+                (9..10, Provenance::Unknown),
+            ]
+        );
     }
 }
@@ -0,0 +1,79 @@
+use crate::{BinaryDiff, BinaryDiffChunk, PositionedChunk};
+
+// Chunks sorted by offset in original binary to look up chunks with binary search
+pub(super) struct OriginalIndex<'a> {
+    // NOTE: Same(), Delete() and Replace() chunks consume each byte of original binary exactly once
+    chunks: Vec<PositionedChunk<'a>>,
+    // Copy() chunks may take the same bytes of original binary many times
+    copies: Vec<PositionedChunk<'a>>,
+}
+
+impl<'a> OriginalIndex<'a> {
+    pub(super) fn new(diff: &'a BinaryDiff) -> Self {
+        let (copies, chunks) = diff
+            .iter_with_positions()
+            .filter(|positioned| match positioned.chunk() {
+                BinaryDiffChunk::Copy(_, _, _) => true,
+                _ => !positioned.original_range().is_empty(),
+            })
+            .partition(|positioned| matches!(positioned.chunk(), BinaryDiffChunk::Copy(_, _, _)));
+        Self { chunks, copies }
+    }
+
+    // Returns chunk that consumes `offset` of original binary
+    pub(super) fn find(&self, offset: usize) -> Option<&PositionedChunk<'a>> {
+        let index = self
+            .chunks
+            .partition_point(|positioned| positioned.original_range().start <= offset);
+        index
+            .checked_sub(1)
+            .map(|index| &self.chunks[index])
+            .filter(|positioned| positioned.original_range().contains(&offset))
+    }
+
+    // Returns offsets of patched binary that Copy() chunks take `offset` of original binary to
+    pub(super) fn copied_to(&self, offset: usize) -> impl Iterator<Item = usize> + '_ {
+        self.copies
+            .iter()
+            .filter_map(move |positioned| match positioned.chunk() {
+                BinaryDiffChunk::Copy(_, source, length)
+                    if (*source..source + length).contains(&offset) =>
+                {
+                    Some(positioned.patched_range().start + offset - source)
+                }
+                _ => None,
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::OriginalIndex;
+    use crate::binary_diff::binary_diff_chunk::BinaryDiffChunk::{Copy, Delete, Insert, Same};
+    use crate::BinaryDiff;
+
+    #[test]
+    fn test_original_index() {
+        let diff = BinaryDiff::from(&vec![
+            Same(0, 4),
+            Delete(4, 2),
+            Insert(6, vec![0, 1]),
+            Same(6, 2),
+            Copy(8, 3, 2),
+        ]);
+        let index = OriginalIndex::new(&diff);
+        let find = |offset| {
+            index
+                .find(offset)
+                .map(|positioned| positioned.chunk().clone())
+        };
+        assert_eq!(find(0), Some(Same(0, 4)));
+        assert_eq!(find(4), Some(Delete(4, 2)));
+        assert_eq!(find(7), Some(Same(6, 2)));
+        assert_eq!(find(8), None);
+
+        assert_eq!(index.copied_to(3).collect::<Vec<_>>(), vec![8]);
+        assert_eq!(index.copied_to(4).collect::<Vec<_>>(), vec![9]);
+        assert_eq!(index.copied_to(5).next(), None);
+    }
+}
@@ -0,0 +1,81 @@
+use crate::{BinaryDiff, PositionedChunk};
+use std::ops::Range;
+
+// Chunks sorted by offset in patched binary to look up chunks with binary search
+pub(crate) struct PatchedIndex<'a> {
+    // NOTE: Delete() chunks are excluded since they occupy no bytes in patched binary
+    chunks: Vec<PositionedChunk<'a>>,
+}
+
+impl<'a> PatchedIndex<'a> {
+    pub(crate) fn new(diff: &'a BinaryDiff) -> Self {
+        Self {
+            chunks: diff
+                .iter_with_positions()
+                .filter(|positioned| !positioned.patched_range().is_empty())
+                .collect(),
+        }
+    }
+
+    // Returns chunk that introduces `offset` of patched binary
+    pub(crate) fn find(&self, offset: usize) -> Option<&PositionedChunk<'a>> {
+        let index = self
+            .chunks
+            .partition_point(|positioned| positioned.patched_range().start <= offset);
+        index
+            .checked_sub(1)
+            .map(|index| &self.chunks[index])
+            .filter(|positioned| positioned.patched_range().contains(&offset))
+    }
+
+    // Returns chunks that overlap with `range` of patched binary in ascending order
+    pub(crate) fn overlapping(&self, range: Range<usize>) -> &[PositionedChunk<'a>] {
+        let start = self
+            .chunks
+            .partition_point(|positioned| positioned.patched_range().end <= range.start);
+        let end = self
+            .chunks
+            .partition_point(|positioned| positioned.patched_range().start < range.end);
+        &self.chunks[start..std::cmp::max(start, end)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PatchedIndex;
+    use crate::binary_diff::binary_diff_chunk::BinaryDiffChunk::{Delete, Insert, Same};
+    use crate::BinaryDiff;
+
+    #[test]
+    fn test_patched_index() {
+        let diff = BinaryDiff::from(&vec![
+            Same(0, 4),
+            Delete(4, 2),
+            Insert(6, vec![0, 1]),
+            Same(6, 2),
+        ]);
+        let index = PatchedIndex::new(&diff);
+        let find = |offset| {
+            index
+                .find(offset)
+                .map(|positioned| positioned.chunk().clone())
+        };
+        assert_eq!(find(0), Some(Same(0, 4)));
+        assert_eq!(find(3), Some(Same(0, 4)));
+        assert_eq!(find(4), Some(Insert(6, vec![0, 1])));
+        assert_eq!(find(7), Some(Same(6, 2)));
+        assert_eq!(find(8), None);
+
+        let overlapping = |start, end| {
+            index
+                .overlapping(start..end)
+                .iter()
+                .map(|positioned| positioned.chunk().clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(overlapping(3, 5), vec![Same(0, 4), Insert(6, vec![0, 1])]);
+        assert_eq!(overlapping(4, 6), vec![Insert(6, vec![0, 1])]);
+        assert_eq!(overlapping(4, 4), vec![]);
+        assert_eq!(overlapping(8, 10), vec![]);
+    }
+}
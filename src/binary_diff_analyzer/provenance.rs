@@ -0,0 +1,107 @@
+use crate::binary_diff_analyzer::derives_from::DerivesFromRange;
+use crate::BinaryDiffChunk;
+use std::ops::Range;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Provenance {
+    // Taken from original binary. Holds offset of original binary of the first byte
+    Preserved(usize),
+    Inserted,
+    Replaced,
+    // Derives from no chunks, i.e. the diff is not of this patched binary
+    Unknown,
+}
+
+// Contiguous bytes of patched binary that have the same provenance
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ProvenanceRun {
+    pub(crate) patched_range: Range<usize>,
+    pub(crate) provenance: Provenance,
+}
+
+impl ProvenanceRun {
+    pub fn patched_range(&self) -> Range<usize> {
+        self.patched_range.clone()
+    }
+
+    pub fn provenance(&self) -> Provenance {
+        self.provenance
+    }
+
+    // Returns range of original binary that preserved bytes are taken from
+    pub fn original_range(&self) -> Option<Range<usize>> {
+        match self.provenance {
+            Provenance::Preserved(offset) => Some(offset..offset + self.patched_range.len()),
+            _ => None,
+        }
+    }
+}
+
+impl<'a> From<&DerivesFromRange<'a>> for ProvenanceRun {
+    fn from(derives_from: &DerivesFromRange<'a>) -> Self {
+        let provenance = match (derives_from.chunk(), derives_from.original_range()) {
+            (_, Some(original_range)) => Provenance::Preserved(original_range.start),
+            (Some(BinaryDiffChunk::Insert(_, _)), _) => Provenance::Inserted,
+            (Some(BinaryDiffChunk::Replace(_, _, _)), _) => Provenance::Replaced,
+            _ => Provenance::Unknown,
+        };
+        Self {
+            patched_range: derives_from.patched_range(),
+            provenance,
+        }
+    }
+}
+
+// Merges adjacent runs of the same provenance. Preserved runs are merged only if they are also adjacent in original binary
+pub(super) fn merge_provenance_runs(runs: Vec<ProvenanceRun>) -> Vec<ProvenanceRun> {
+    let mut merged: Vec<ProvenanceRun> = Vec::with_capacity(runs.len());
+    for run in runs.into_iter() {
+        if let Some(last) = merged.last_mut() {
+            let mergeable = match (last.provenance, run.provenance) {
+                (Provenance::Preserved(a), Provenance::Preserved(b)) => {
+                    a + last.patched_range.len() == b
+                }
+                (a, b) => a == b,
+            };
+            if mergeable && last.patched_range.end == run.patched_range.start {
+                last.patched_range.end = run.patched_range.end;
+                continue;
+            }
+        }
+        merged.push(run);
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{merge_provenance_runs, Provenance, ProvenanceRun};
+
+    #[test]
+    fn test_merge_provenance_runs() {
+        let run = |patched_range, provenance| ProvenanceRun {
+            patched_range,
+            provenance,
+        };
+        assert_eq!(
+            merge_provenance_runs(vec![
+                run(0..2, Provenance::Preserved(0)),
+                run(2..4, Provenance::Preserved(2)),
+                run(4..6, Provenance::Preserved(0)),
+                run(6..7, Provenance::Inserted),
+                run(7..8, Provenance::Inserted),
+                run(8..9, Provenance::Replaced),
+            ]),
+            vec![
+                run(0..4, Provenance::Preserved(0)),
+                run(4..6, Provenance::Preserved(0)),
+                run(6..8, Provenance::Inserted),
+                run(8..9, Provenance::Replaced),
+            ]
+        );
+        assert_eq!(
+            run(4..6, Provenance::Preserved(8)).original_range(),
+            Some(8..10)
+        );
+    }
+}
@@ -1,10 +1,924 @@
 extern crate binary_diff;
+#[macro_use]
 extern crate clap;
 
-use binary_diff::{BinaryDiff, BinaryDiffAnalyzer, BinaryDiffChunk};
-use clap::{App, Arg};
-use std::io::BufReader;
-use std::path::Path;
+use binary_diff::{
+    BinaryDiff, BinaryDiffAnalyzer, BinaryDiffChunk, BinaryDiffError, CorpusIndex, CorpusSketches,
+    DiffEngine, DiffOptions, DiffStats, Minimizer, MutationClassifier, MutationLabel, NearestEntry,
+    Provenance, ProvenanceRun, SpliceSource, TieBreaking,
+};
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+use serde::Serialize;
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::time::{Duration, Instant};
+
+// Input file. `-` means stdin
+enum Input {
+    File(std::fs::File),
+    Stdin(std::io::Stdin),
+}
+
+impl Input {
+    fn open(path: &str) -> std::io::Result<Self> {
+        if path == "-" {
+            Ok(Input::Stdin(std::io::stdin()))
+        } else {
+            std::fs::File::open(path).map(Input::File)
+        }
+    }
+
+    // Pipes such as `<(gunzip -c a.gz)` are files but not seekable
+    fn is_seekable(&mut self) -> bool {
+        match self {
+            Input::File(file) => file.stream_position().is_ok(),
+            Input::Stdin(_) => false,
+        }
+    }
+}
+
+impl Read for Input {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self {
+            Input::File(file) => file.read(buf),
+            Input::Stdin(stdin) => stdin.read(buf),
+        }
+    }
+}
+
+impl Seek for Input {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        match self {
+            Input::File(file) => file.seek(pos),
+            Input::Stdin(_) => Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "stdin is not seekable",
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum Format {
+    Text,
+    Json,
+    Jsonl,
+    Csv,
+}
+
+const CHUNK_CSV_HEADER: &str =
+    "kind,original_offset,patched_offset,original_length,patched_length,source_offset,bytes";
+
+// Chunk in machine-readable formats. Offsets and lengths are decimal, and bytes are hex string
+#[derive(Debug, Serialize)]
+struct ChunkRecord {
+    kind: String,
+    original_offset: usize,
+    patched_offset: usize,
+    original_length: usize,
+    patched_length: usize,
+    // Only Copy chunk has source offset
+    source_offset: Option<usize>,
+    bytes: String,
+}
+
+impl ChunkRecord {
+    fn new(chunk: &BinaryDiffChunk, patched_offset: usize) -> Self {
+        let (source_offset, bytes) = match chunk {
+            BinaryDiffChunk::Insert(_, bytes) | BinaryDiffChunk::Replace(_, _, bytes) => (
+                None,
+                bytes.iter().map(|byte| format!("{:02x}", byte)).collect(),
+            ),
+            BinaryDiffChunk::Copy(_, source, _) => (Some(*source), String::new()),
+            _ => (None, String::new()),
+        };
+        Self {
+            kind: chunk.name().to_string(),
+            original_offset: *chunk.offset(),
+            patched_offset,
+            original_length: chunk.original_length(),
+            patched_length: chunk.patched_length(),
+            source_offset,
+            bytes,
+        }
+    }
+
+    fn to_csv(&self) -> String {
+        format!(
+            "{},{},{},{},{},{},{}",
+            self.kind,
+            self.original_offset,
+            self.patched_offset,
+            self.original_length,
+            self.patched_length,
+            self.source_offset
+                .map_or_else(String::new, |offset| offset.to_string()),
+            self.bytes
+        )
+    }
+}
+
+// Result of --offset analysis in machine-readable formats
+#[derive(Debug, Serialize)]
+struct OffsetRecord {
+    offset: usize,
+    original_position: Option<usize>,
+    // null if `offset` derives from no chunks
+    chunk: Option<ChunkRecord>,
+}
+
+// Result of --original-offset analysis in machine-readable formats
+#[derive(Debug, Serialize)]
+struct OriginalOffsetRecord {
+    original_offset: usize,
+    // Empty if the byte does not survive in patched file
+    patched_offsets: Vec<usize>,
+    removed: bool,
+    // null if `original_offset` is out of original file
+    chunk: Option<ChunkRecord>,
+}
+
+// Provenance run in machine-readable formats
+#[derive(Debug, Serialize)]
+struct ProvenanceRecord {
+    kind: String,
+    patched_offset: usize,
+    length: usize,
+    // Only preserved bytes have original offset
+    original_offset: Option<usize>,
+}
+
+impl ProvenanceRecord {
+    fn new(run: &ProvenanceRun) -> Self {
+        let kind = match run.provenance() {
+            Provenance::Preserved(_) => "Preserved",
+            Provenance::Inserted => "Inserted",
+            Provenance::Replaced => "Replaced",
+            Provenance::Unknown => "Unknown",
+        };
+        Self {
+            kind: kind.to_string(),
+            patched_offset: run.patched_range().start,
+            length: run.patched_range().len(),
+            original_offset: run.original_range().map(|range| range.start),
+        }
+    }
+}
+
+// Values of provenance mask. The mask has one byte per byte of patched file
+const MASK_PRESERVED: u8 = 0x00;
+const MASK_INSERTED: u8 = 0x01;
+const MASK_REPLACED: u8 = 0x02;
+const MASK_UNKNOWN: u8 = 0xff;
+
+fn write_provenance_mask(runs: &[ProvenanceRun], path: &str) -> std::io::Result<()> {
+    let mut writer = BufWriter::new(std::fs::File::create(path)?);
+    for run in runs.iter() {
+        let value = match run.provenance() {
+            Provenance::Preserved(_) => MASK_PRESERVED,
+            Provenance::Inserted => MASK_INSERTED,
+            Provenance::Replaced => MASK_REPLACED,
+            Provenance::Unknown => MASK_UNKNOWN,
+        };
+        writer.write_all(&vec![value; run.patched_range().len()])?;
+    }
+    writer.flush()
+}
+
+fn print_provenance(runs: &[ProvenanceRun], format: Format) {
+    let records: Vec<ProvenanceRecord> = runs.iter().map(ProvenanceRecord::new).collect();
+    match format {
+        Format::Text => {
+            for record in records.iter() {
+                match record.original_offset {
+                    Some(original_offset) => println!(
+                        "{:<9}(offset={:#x}, length={:#x}, original_offset={:#x})",
+                        record.kind, record.patched_offset, record.length, original_offset
+                    ),
+                    None => println!(
+                        "{:<9}(offset={:#x}, length={:#x})",
+                        record.kind, record.patched_offset, record.length
+                    ),
+                }
+            }
+        }
+        Format::Json => println!("{}", serde_json::to_string_pretty(&records).unwrap()),
+        Format::Jsonl => {
+            for record in records.iter() {
+                println!("{}", serde_json::to_string(record).unwrap());
+            }
+        }
+        Format::Csv => {
+            println!("kind,patched_offset,length,original_offset");
+            for record in records.iter() {
+                println!(
+                    "{},{},{},{}",
+                    record.kind,
+                    record.patched_offset,
+                    record.length,
+                    record
+                        .original_offset
+                        .map_or_else(String::new, |offset| offset.to_string())
+                );
+            }
+        }
+    }
+}
+
+// Mutation label in machine-readable formats
+#[derive(Debug, Serialize)]
+struct MutationRecord {
+    original_offset: usize,
+    original_length: usize,
+    patched_offset: usize,
+    patched_length: usize,
+    mutation: String,
+    confidence: f64,
+}
+
+impl MutationRecord {
+    fn new(label: &MutationLabel) -> Self {
+        Self {
+            original_offset: label.original_range().start,
+            original_length: label.original_range().len(),
+            patched_offset: label.patched_range().start,
+            patched_length: label.patched_range().len(),
+            mutation: label.mutation().to_string(),
+            confidence: label.confidence(),
+        }
+    }
+}
+
+fn print_mutations(labels: &[MutationLabel], format: Format) {
+    match format {
+        Format::Text => {
+            let mut previous: Option<&[BinaryDiffChunk]> = None;
+            for label in labels.iter() {
+                // Labels of the same change share chunks
+                if previous.map_or(true, |chunks| !std::ptr::eq(chunks, label.chunks())) {
+                    for chunk in label.chunks().iter() {
+                        println!("{}", chunk);
+                    }
+                    previous = Some(label.chunks());
+                }
+                println!(
+                    "    {} (confidence={:.2})",
+                    label.mutation(),
+                    label.confidence()
+                );
+            }
+        }
+        Format::Json => {
+            let records: Vec<MutationRecord> = labels.iter().map(MutationRecord::new).collect();
+            println!("{}", serde_json::to_string_pretty(&records).unwrap())
+        }
+        Format::Jsonl => {
+            for label in labels.iter() {
+                println!(
+                    "{}",
+                    serde_json::to_string(&MutationRecord::new(label)).unwrap()
+                );
+            }
+        }
+        Format::Csv => {
+            println!(
+                "original_offset,original_length,patched_offset,patched_length,mutation,confidence"
+            );
+            for record in labels.iter().map(MutationRecord::new) {
+                // NOTE: Mutation contains commas
+                println!(
+                    "{},{},{},{},\"{}\",{}",
+                    record.original_offset,
+                    record.original_length,
+                    record.patched_offset,
+                    record.patched_length,
+                    record.mutation,
+                    record.confidence
+                );
+            }
+        }
+    }
+}
+
+// Splice source in machine-readable formats
+#[derive(Debug, Serialize)]
+struct SpliceSourceRecord {
+    original_offset: usize,
+    patched_offset: usize,
+    length: usize,
+    corpus_file: String,
+    corpus_offset: usize,
+}
+
+// Lists files in `dir` other than `excluded` in order of path
+fn list_corpus(dir: &str, excluded: &[PathBuf]) -> std::io::Result<Vec<PathBuf>> {
+    let mut paths = vec![];
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_file() && !excluded.contains(&path.canonicalize()?) {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+    Ok(paths)
+}
+
+// Reads regular files in `dir` except `excluded` ones, e.g. the compared files themselves
+fn load_corpus(dir: &str, excluded: &[PathBuf]) -> std::io::Result<(CorpusIndex, Vec<PathBuf>)> {
+    let paths = list_corpus(dir, excluded)?;
+    let mut corpus = CorpusIndex::new();
+    for path in paths.iter() {
+        corpus.add(std::fs::read(path)?);
+    }
+    Ok((corpus, paths))
+}
+
+fn print_splice_sources(sources: &[SpliceSource], paths: &[PathBuf], format: Format) {
+    let records: Vec<SpliceSourceRecord> = sources
+        .iter()
+        .map(|source| SpliceSourceRecord {
+            original_offset: *source.chunk().chunk().offset(),
+            patched_offset: source.patched_range().start,
+            length: source.payload_range().len(),
+            corpus_file: paths[source.entry()].display().to_string(),
+            corpus_offset: source.entry_range().start,
+        })
+        .collect();
+    match format {
+        Format::Text => {
+            let mut previous = None;
+            for (source, record) in sources.iter().zip(records.iter()) {
+                // Sources of the same chunk are printed under the chunk
+                if previous != Some(source.chunk()) {
+                    println!("{}", source.chunk().chunk());
+                    previous = Some(source.chunk());
+                }
+                println!(
+                    "    <- {} (offset={:#x}, length={:#x}) at offset={:#x} of patched file",
+                    record.corpus_file, record.corpus_offset, record.length, record.patched_offset
+                );
+            }
+        }
+        Format::Json => println!("{}", serde_json::to_string_pretty(&records).unwrap()),
+        Format::Jsonl => {
+            for record in records.iter() {
+                println!("{}", serde_json::to_string(record).unwrap());
+            }
+        }
+        Format::Csv => {
+            println!("original_offset,patched_offset,length,corpus_file,corpus_offset");
+            for record in records.iter() {
+                println!(
+                    "{},{},{},\"{}\",{}",
+                    record.original_offset,
+                    record.patched_offset,
+                    record.length,
+                    record.corpus_file.replace('"', "\"\""),
+                    record.corpus_offset
+                );
+            }
+        }
+    }
+}
+
+const STAT_CSV_HEADER: &str = concat!(
+    "original_length,patched_length,kept_bytes,inserted_bytes,deleted_bytes,replaced_bytes,",
+    "copied_bytes,same_chunks,insert_chunks,delete_chunks,replace_chunks,copy_chunks,",
+    "longest_same_length,edit_distance,similarity"
+);
+
+// Result of --stat in machine-readable formats
+#[derive(Debug, Serialize)]
+struct StatRecord {
+    original_length: usize,
+    patched_length: usize,
+    kept_bytes: usize,
+    inserted_bytes: usize,
+    deleted_bytes: usize,
+    replaced_bytes: usize,
+    copied_bytes: usize,
+    same_chunks: usize,
+    insert_chunks: usize,
+    delete_chunks: usize,
+    replace_chunks: usize,
+    copy_chunks: usize,
+    longest_same_length: usize,
+    edit_distance: usize,
+    similarity: f64,
+}
+
+impl StatRecord {
+    fn new(stats: &DiffStats) -> Self {
+        Self {
+            original_length: stats.original_length(),
+            patched_length: stats.patched_length(),
+            kept_bytes: stats.kept_bytes(),
+            inserted_bytes: stats.inserted_bytes(),
+            deleted_bytes: stats.deleted_bytes(),
+            replaced_bytes: stats.replaced_bytes(),
+            copied_bytes: stats.copied_bytes(),
+            same_chunks: stats.same_chunks(),
+            insert_chunks: stats.insert_chunks(),
+            delete_chunks: stats.delete_chunks(),
+            replace_chunks: stats.replace_chunks(),
+            copy_chunks: stats.copy_chunks(),
+            longest_same_length: stats.longest_same_length(),
+            edit_distance: stats.edit_distance(),
+            similarity: stats.similarity(),
+        }
+    }
+}
+
+fn print_stats(stats: &DiffStats, format: Format) {
+    let record = StatRecord::new(stats);
+    match format {
+        Format::Text => {
+            println!(
+                "Same   : {} chunks, {:#x} bytes (longest={:#x})",
+                record.same_chunks, record.kept_bytes, record.longest_same_length
+            );
+            println!(
+                "Insert : {} chunks, {:#x} bytes",
+                record.insert_chunks, record.inserted_bytes
+            );
+            println!(
+                "Delete : {} chunks, {:#x} bytes",
+                record.delete_chunks, record.deleted_bytes
+            );
+            println!(
+                "Replace: {} chunks, {:#x} bytes",
+                record.replace_chunks, record.replaced_bytes
+            );
+            println!(
+                "Copy   : {} chunks, {:#x} bytes",
+                record.copy_chunks, record.copied_bytes
+            );
+            println!(
+                "original_length={:#x}, patched_length={:#x}, edit_distance={:#x}, similarity={:.3}",
+                record.original_length,
+                record.patched_length,
+                record.edit_distance,
+                record.similarity
+            );
+        }
+        Format::Json => println!("{}", serde_json::to_string_pretty(&record).unwrap()),
+        Format::Jsonl => println!("{}", serde_json::to_string(&record).unwrap()),
+        Format::Csv => {
+            println!("{}", STAT_CSV_HEADER);
+            println!(
+                "{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
+                record.original_length,
+                record.patched_length,
+                record.kept_bytes,
+                record.inserted_bytes,
+                record.deleted_bytes,
+                record.replaced_bytes,
+                record.copied_bytes,
+                record.same_chunks,
+                record.insert_chunks,
+                record.delete_chunks,
+                record.replace_chunks,
+                record.copy_chunks,
+                record.longest_same_length,
+                record.edit_distance,
+                record.similarity
+            );
+        }
+    }
+}
+
+// Default number of corpus entries to be diffed by `nearest` subcommand
+const NEAREST_CANDIDATES: &str = "8";
+
+// Nearest corpus entry in machine-readable formats
+#[derive(Debug, Serialize)]
+struct NearestRecord {
+    rank: usize,
+    corpus_file: String,
+    edit_cost: usize,
+    similarity: f64,
+}
+
+fn print_nearest(nearest: &[NearestEntry], paths: &[PathBuf], format: Format) {
+    let records: Vec<NearestRecord> = nearest
+        .iter()
+        .enumerate()
+        .map(|(i, nearest)| NearestRecord {
+            rank: i + 1,
+            corpus_file: paths[nearest.entry()].display().to_string(),
+            edit_cost: nearest.edit_cost(),
+            similarity: nearest.similarity(),
+        })
+        .collect();
+    match format {
+        Format::Text => {
+            for record in records.iter() {
+                println!(
+                    "{}. {} (edit_cost={:#x}, similarity={:.3})",
+                    record.rank, record.corpus_file, record.edit_cost, record.similarity
+                );
+            }
+        }
+        Format::Json => println!("{}", serde_json::to_string_pretty(&records).unwrap()),
+        Format::Jsonl => {
+            for record in records.iter() {
+                println!("{}", serde_json::to_string(record).unwrap());
+            }
+        }
+        Format::Csv => {
+            println!("rank,corpus_file,edit_cost,similarity");
+            for record in records.iter() {
+                println!(
+                    "{},\"{}\",{},{}",
+                    record.rank,
+                    record.corpus_file.replace('"', "\"\""),
+                    record.edit_cost,
+                    record.similarity
+                );
+            }
+        }
+    }
+}
+
+// `nearest CRASH CORPUS_DIR` subcommand
+fn nearest(matches: &ArgMatches, format: Format) {
+    let crash_path = matches.value_of("CRASH").unwrap();
+    let crash = match std::fs::read(crash_path) {
+        Ok(crash) => crash,
+        Err(why) => {
+            eprintln!("[!] File {} does not exist: {:?}", crash_path, why);
+            return;
+        }
+    };
+    let dir = matches.value_of("CORPUS_DIR").unwrap();
+    let excluded: Vec<PathBuf> = Path::new(crash_path).canonicalize().into_iter().collect();
+    // NOTE: Only sketches are kept in memory since corpus (e.g. queue of AFL) can be large
+    let sketch_corpus = || -> std::io::Result<(CorpusSketches, Vec<PathBuf>)> {
+        let paths = list_corpus(dir, &excluded)?;
+        let mut sketches = CorpusSketches::new();
+        for path in paths.iter() {
+            sketches.add(&std::fs::read(path)?);
+        }
+        Ok((sketches, paths))
+    };
+    let (sketches, paths) = match sketch_corpus() {
+        Ok(sketches) => sketches,
+        Err(why) => {
+            eprintln!("[!] Failed to read corpus {}: {:?}", dir, why);
+            return;
+        }
+    };
+    let candidates = value_t!(matches, "CANDIDATES", usize).unwrap_or_else(|e| e.exit());
+    let nearest = sketches.nearest(&crash, candidates, |entry| {
+        std::fs::read(&paths[entry]).map_err(BinaryDiffError::IoError)
+    });
+    match nearest {
+        Ok(nearest) => print_nearest(&nearest, &paths, format),
+        Err(why) => eprintln!("[!] Failed to rank corpus {}: {:?}", dir, why),
+    }
+}
+
+// Runs `command` with `bytes` as input, and returns whether it reproduces the crash.
+// `@@` in `command` is replaced by `input_path` where the input is written, otherwise the input is given to stdin (like AFL).
+// Crash is reproduced if the command is killed by a signal, or exits with `exit_code` (any non-zero code if None).
+// Commands running longer than `timeout` are killed with their children and regarded as not reproducing.
+fn run_oracle(
+    command: &str,
+    bytes: &[u8],
+    input_path: Option<&Path>,
+    exit_code: Option<i32>,
+    timeout: Option<Duration>,
+) -> std::io::Result<bool> {
+    let mut shell = Command::new("sh");
+    shell.arg("-c");
+    match input_path {
+        Some(input_path) => {
+            std::fs::write(input_path, bytes)?;
+            shell
+                .arg(command.replace("@@", &input_path.display().to_string()))
+                .stdin(Stdio::null())
+        }
+        None => shell.arg(command).stdin(Stdio::piped()),
+    };
+    // Runs the command in its own process group to kill whole of it on timeout
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut shell, 0);
+    let mut child = shell.stdout(Stdio::null()).stderr(Stdio::null()).spawn()?;
+
+    let status = std::thread::scope(|scope| -> std::io::Result<Option<ExitStatus>> {
+        if let Some(mut stdin) = child.stdin.take() {
+            // NOTE: Writing blocks until the command reads stdin, which it may never do.
+            // The writer fails once the command exits or is killed.
+            scope.spawn(move || {
+                let _ = stdin.write_all(bytes);
+            });
+        }
+
+        let start = Instant::now();
+        loop {
+            if let Some(status) = child.try_wait()? {
+                return Ok(Some(status));
+            }
+            if timeout.is_some_and(|timeout| start.elapsed() > timeout) {
+                kill_process_group(&mut child)?;
+                child.wait()?;
+                return Ok(None);
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    })?;
+    Ok(match status.map(|status| (status.code(), exit_code)) {
+        None => false,
+        Some((None, _)) => true,
+        Some((Some(code), Some(exit_code))) => code == exit_code,
+        Some((Some(code), None)) => code != 0,
+    })
+}
+
+#[cfg(unix)]
+fn kill_process_group(child: &mut Child) -> std::io::Result<()> {
+    // SAFETY: kill(2) has no memory safety requirements. The process group is the one of `child`
+    // since it is not waited yet.
+    if unsafe { libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL) } == 0 {
+        Ok(())
+    } else {
+        child.kill()
+    }
+}
+
+#[cfg(not(unix))]
+fn kill_process_group(child: &mut Child) -> std::io::Result<()> {
+    child.kill()
+}
+
+// Creates a directory only the current user can access, to write inputs of the oracle safely
+fn create_private_dir() -> std::io::Result<PathBuf> {
+    let mut builder = std::fs::DirBuilder::new();
+    #[cfg(unix)]
+    std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.subsec_nanos());
+    let mut attempt = 0u32;
+    loop {
+        let dir = std::env::temp_dir().join(format!(
+            "binary-diff-minimize-{}-{}-{}",
+            std::process::id(),
+            nanos,
+            attempt
+        ));
+        // NOTE: Fails if the path already exists, even as a symlink
+        match builder.create(&dir) {
+            Ok(()) => return Ok(dir),
+            Err(why) if why.kind() == std::io::ErrorKind::AlreadyExists && attempt < 16 => {
+                attempt += 1
+            }
+            Err(why) => return Err(why),
+        }
+    }
+}
+
+// `minimize ORIGINAL CRASH --command COMMAND` subcommand
+fn minimize(matches: &ArgMatches, format: Format) {
+    let (original_path, crash_path) = (
+        matches.value_of("ORIGINAL").unwrap(),
+        matches.value_of("CRASH").unwrap(),
+    );
+    let (original, crash) = match (std::fs::read(original_path), std::fs::read(crash_path)) {
+        (Ok(original), Ok(crash)) => (original, crash),
+        (Err(why), _) => {
+            eprintln!("[!] File {} does not exist: {:?}", original_path, why);
+            return;
+        }
+        (_, Err(why)) => {
+            eprintln!("[!] File {} does not exist: {:?}", crash_path, why);
+            return;
+        }
+    };
+    let command = matches.value_of("COMMAND").unwrap();
+    let exit_code = if matches.is_present("EXIT_CODE") {
+        Some(value_t!(matches, "EXIT_CODE", i32).unwrap_or_else(|e| e.exit()))
+    } else {
+        None
+    };
+    let timeout = if matches.is_present("TIMEOUT") {
+        Some(Duration::from_secs_f64(
+            value_t!(matches, "TIMEOUT", f64).unwrap_or_else(|e| e.exit()),
+        ))
+    } else {
+        None
+    };
+    let output = matches.value_of("OUTPUT").map_or_else(
+        || format!("{}.min", crash_path),
+        |output| output.to_string(),
+    );
+    // The input file is needed only if the command reads it instead of stdin
+    let input_dir = if command.contains("@@") {
+        match create_private_dir() {
+            Ok(dir) => Some(dir),
+            Err(why) => {
+                eprintln!("[!] Failed to create temporary directory: {:?}", why);
+                return;
+            }
+        }
+    } else {
+        None
+    };
+    let input_path = input_dir.as_ref().map(|dir| dir.join("input"));
+
+    let oracle = |bytes: &[u8]| match run_oracle(
+        command,
+        bytes,
+        input_path.as_deref(),
+        exit_code,
+        timeout,
+    ) {
+        Ok(reproduced) => reproduced,
+        Err(why) => {
+            eprintln!("[!] Failed to run {}: {:?}", command, why);
+            false
+        }
+    };
+    let minimized =
+        Minimizer::new(&original, &crash, oracle).and_then(|minimizer| minimizer.minimize());
+    if let Some(input_dir) = input_dir.as_ref() {
+        let _ = std::fs::remove_dir_all(input_dir);
+    }
+    let minimized = match minimized {
+        Ok(minimized) => minimized,
+        Err(why) => {
+            eprintln!("[!] Failed to minimize {}: {:?}", crash_path, why);
+            return;
+        }
+    };
+
+    match minimized {
+        Some(minimized) => {
+            if let Err(why) = std::fs::write(&output, minimized.bytes()) {
+                eprintln!("[!] Failed to write {}: {:?}", output, why);
+                return;
+            }
+            eprintln!(
+                "[*] Minimized with {} tests. Wrote {:#x} bytes to {}",
+                minimized.tests(),
+                minimized.bytes().len(),
+                output
+            );
+            print_chunks(&minimized.diff().enhance(), format, false);
+        }
+        None => eprintln!("[!] {} does not reproduce the crash", crash_path),
+    }
+}
+
+fn print_chunks(diff: &BinaryDiff, format: Format, print_same_chunks: bool) {
+    let mut records = vec![];
+    for positioned in diff.iter_with_positions() {
+        let chunk = positioned.chunk();
+        let print = match chunk {
+            BinaryDiffChunk::Same(_, _) => print_same_chunks,
+            _ => true,
+        };
+        if print {
+            match format {
+                Format::Text => println!("{}", chunk),
+                _ => records.push(ChunkRecord::new(chunk, positioned.patched_range().start)),
+            }
+        }
+    }
+
+    match format {
+        Format::Text => (),
+        Format::Json => println!("{}", serde_json::to_string_pretty(&records).unwrap()),
+        Format::Jsonl => {
+            for record in records.iter() {
+                println!("{}", serde_json::to_string(record).unwrap());
+            }
+        }
+        Format::Csv => {
+            println!("{}", CHUNK_CSV_HEADER);
+            for record in records.iter() {
+                println!("{}", record.to_csv());
+            }
+        }
+    }
+}
+
+fn print_offset_record(record: &OffsetRecord, format: Format) {
+    match format {
+        Format::Text => (),
+        Format::Json => println!("{}", serde_json::to_string_pretty(record).unwrap()),
+        Format::Jsonl => println!("{}", serde_json::to_string(record).unwrap()),
+        Format::Csv => {
+            println!("offset,original_position,{}", CHUNK_CSV_HEADER);
+            println!(
+                "{},{},{}",
+                record.offset,
+                record
+                    .original_position
+                    .map_or_else(String::new, |position| position.to_string()),
+                record
+                    .chunk
+                    .as_ref()
+                    .map_or_else(|| ",,,,,,".to_string(), |chunk| chunk.to_csv())
+            );
+        }
+    }
+}
+
+fn print_original_offset_record(record: &OriginalOffsetRecord, format: Format) {
+    match format {
+        Format::Text => (),
+        Format::Json => println!("{}", serde_json::to_string_pretty(record).unwrap()),
+        Format::Jsonl => println!("{}", serde_json::to_string(record).unwrap()),
+        Format::Csv => {
+            println!(
+                "original_offset,patched_offsets,removed,{}",
+                CHUNK_CSV_HEADER
+            );
+            // NOTE: Patched offsets are separated by spaces
+            println!(
+                "{},{},{},{}",
+                record.original_offset,
+                record
+                    .patched_offsets
+                    .iter()
+                    .map(|offset| offset.to_string())
+                    .collect::<Vec<_>>()
+                    .join(" "),
+                record.removed,
+                record
+                    .chunk
+                    .as_ref()
+                    .map_or_else(|| ",,,,,,".to_string(), |chunk| chunk.to_csv())
+            );
+        }
+    }
+}
+
+fn diff_options(matches: &ArgMatches) -> DiffOptions {
+    let mut options = DiffOptions::new();
+    if let Some(engine) = matches.value_of("ENGINE") {
+        options = options.engine(match engine {
+            "myers" => DiffEngine::Myers,
+            "suffix-array" => DiffEngine::SuffixArray,
+            "rolling-hash" => DiffEngine::RollingHash,
+            "streaming" => DiffEngine::Streaming,
+            _ => DiffEngine::Greedy,
+        });
+    }
+    if matches.is_present("MYERS_MAX_SIZE") {
+        options = options.myers_max_size(
+            value_t!(matches, "MYERS_MAX_SIZE", usize).unwrap_or_else(|e| e.exit()),
+        );
+    }
+    if matches.is_present("SUFFIX_ARRAY_MIN_MATCH_LENGTH") {
+        options = options.suffix_array_min_match_length(
+            value_t!(matches, "SUFFIX_ARRAY_MIN_MATCH_LENGTH", usize).unwrap_or_else(|e| e.exit()),
+        );
+    }
+    if matches.is_present("BLOCK_SIZE") {
+        options = options.rolling_hash_block_size(
+            value_t!(matches, "BLOCK_SIZE", usize).unwrap_or_else(|e| e.exit()),
+        );
+    }
+    if matches.is_present("LOOKAHEAD") {
+        options = options.streaming_lookahead(
+            value_t!(matches, "LOOKAHEAD", usize).unwrap_or_else(|e| e.exit()),
+        );
+    }
+    if matches.is_present("DELETE_WINDOWS") {
+        options = options.delete_windows(
+            &values_t!(matches, "DELETE_WINDOWS", usize).unwrap_or_else(|e| e.exit()),
+        );
+    }
+    if matches.is_present("INSERT_WINDOWS") {
+        options = options.insert_windows(
+            &values_t!(matches, "INSERT_WINDOWS", usize).unwrap_or_else(|e| e.exit()),
+        );
+    }
+    if matches.is_present("HASH_GRANULARITY") {
+        options = options.hash_granularity(
+            value_t!(matches, "HASH_GRANULARITY", usize).unwrap_or_else(|e| e.exit()),
+        );
+    }
+    if matches.is_present("MIN_MATCH_LENGTH") {
+        options = options.min_match_length(
+            value_t!(matches, "MIN_MATCH_LENGTH", usize).unwrap_or_else(|e| e.exit()),
+        );
+    }
+    if let Some(prefer) = matches.value_of("PREFER") {
+        options = options.tie_breaking(match prefer {
+            "insert" => TieBreaking::PreferInsert,
+            _ => TieBreaking::PreferDelete,
+        });
+    }
+    if matches.is_present("MAX_LOOKAHEAD") {
+        options = options
+            .max_lookahead(value_t!(matches, "MAX_LOOKAHEAD", usize).unwrap_or_else(|e| e.exit()));
+    }
+    options
+}
 
 fn main() {
     env_logger::init();
@@ -13,6 +927,71 @@ fn main() {
         .version("1.0")
         .author("Nao Tomori (@K_atc)")
         .about("Show changes between two binaries. Each of value is hex (16 digit) value")
+        .setting(AppSettings::SubcommandsNegateReqs)
+        .subcommand(
+            SubCommand::with_name("minimize")
+                .about("Reduces a crash towards its original file by applying subsets of diff chunks")
+                .arg(
+                    Arg::with_name("ORIGINAL")
+                        .help("Original file such as seed of the crash")
+                        .required(true)
+                        .index(1),
+                )
+                .arg(
+                    Arg::with_name("CRASH")
+                        .help("Crash file")
+                        .required(true)
+                        .index(2),
+                )
+                .arg(
+                    Arg::with_name("COMMAND")
+                        .long("command")
+                        .help("Shell command to test inputs. `@@` is replaced by path of input file, otherwise input is given to stdin")
+                        .required(true)
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("EXIT_CODE")
+                        .long("exit-code")
+                        .help("Exit code that means crash. Any non-zero exit code or signal means crash by default")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("TIMEOUT")
+                        .long("timeout")
+                        .help("Seconds to kill command. Killed runs do not reproduce crash")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("OUTPUT")
+                        .long("output")
+                        .help("File to write minimized crash. Defaults to CRASH.min")
+                        .takes_value(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("nearest")
+                .about("Ranks files in corpus directory that a crash most likely derives from")
+                .arg(
+                    Arg::with_name("CRASH")
+                        .help("Crash file")
+                        .required(true)
+                        .index(1),
+                )
+                .arg(
+                    Arg::with_name("CORPUS_DIR")
+                        .help("Corpus directory such as queue of AFL")
+                        .required(true)
+                        .index(2),
+                )
+                .arg(
+                    Arg::with_name("CANDIDATES")
+                        .long("candidates")
+                        .help("Number of files of the highest n-gram similarity to be diffed")
+                        .default_value(NEAREST_CANDIDATES)
+                        .takes_value(true),
+                ),
+        )
         .arg(
             Arg::with_name("same")
                 .long("same")
@@ -21,13 +1000,13 @@ fn main() {
         )
         .arg(
             Arg::with_name("FILE1")
-                .help("Original file")
+                .help("Original file. `-` means stdin")
                 .required(true)
                 .index(1),
         )
         .arg(
             Arg::with_name("FILE2")
-                .help("Patched file")
+                .help("Patched file. `-` means stdin")
                 .required(true)
                 .index(2),
         )
@@ -37,31 +1016,223 @@ fn main() {
                 .help("Analyzes given offset of patched file derives from which diff chunk")
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("ORIGINAL_OFFSET")
+                .long("original-offset")
+                .help(
+                    "Analyzes given offset of original file maps to which offsets of patched file",
+                )
+                .conflicts_with("OFFSET")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("PROVENANCE")
+                .long("provenance")
+                .help("Shows provenance (preserved, inserted or replaced) of each byte of patched file")
+                .conflicts_with_all(&["OFFSET", "ORIGINAL_OFFSET"])
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("PROVENANCE_MASK")
+                .long("provenance-mask")
+                .help("Writes provenance of each byte of patched file to given file as one byte per byte")
+                .conflicts_with_all(&["OFFSET", "ORIGINAL_OFFSET"])
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("MUTATIONS")
+                .long("mutations")
+                .help("Labels changes with likely mutation operators of AFL such as bit flip")
+                .conflicts_with_all(&["OFFSET", "ORIGINAL_OFFSET", "PROVENANCE", "PROVENANCE_MASK"])
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("CORPUS")
+                .long("corpus")
+                .help("Finds files in given directory that bytes of Insert and Replace chunks are copied from")
+                .conflicts_with_all(&[
+                    "OFFSET",
+                    "ORIGINAL_OFFSET",
+                    "PROVENANCE",
+                    "PROVENANCE_MASK",
+                    "MUTATIONS",
+                ])
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("STAT")
+                .long("stat")
+                .help("Shows statistics of chunks such as bytes kept and similarity")
+                .conflicts_with_all(&[
+                    "OFFSET",
+                    "ORIGINAL_OFFSET",
+                    "PROVENANCE",
+                    "PROVENANCE_MASK",
+                    "MUTATIONS",
+                    "CORPUS",
+                ])
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("FORMAT")
+                .long("format")
+                .help("Output format. Machine-readable formats have decimal offsets and hex bytes")
+                .possible_values(&["text", "json", "jsonl", "csv"])
+                .default_value("text")
+                .global(true)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("ENGINE")
+                .long("engine")
+                .help("Engine to calculate diff")
+                .possible_values(&[
+                    "greedy",
+                    "myers",
+                    "suffix-array",
+                    "rolling-hash",
+                    "streaming",
+                ])
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("MYERS_MAX_SIZE")
+                .long("myers-max-size")
+                .help("Falls back to greedy engine if size of either of files exceeds this value")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("SUFFIX_ARRAY_MIN_MATCH_LENGTH")
+                .long("suffix-array-min-match-length")
+                .help("Common bytes shorter than this value are ignored by suffix-array engine")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("BLOCK_SIZE")
+                .long("block-size")
+                .help("Size of blocks that rolling-hash engine matches")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("LOOKAHEAD")
+                .long("lookahead")
+                .help("Bytes that streaming engine reads ahead to search end of changes")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("DELETE_WINDOWS")
+                .long("delete-windows")
+                .help("Comma separated windows to search next Same chunk after Delete chunk")
+                .use_delimiter(true)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("INSERT_WINDOWS")
+                .long("insert-windows")
+                .help("Comma separated windows to search next Same chunk after Insert chunk")
+                .use_delimiter(true)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("HASH_GRANULARITY")
+                .long("hash-granularity")
+                .help("Hash size of LCS search in windows")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("MIN_MATCH_LENGTH")
+                .long("min-match-length")
+                .help("Common bytes shorter than this value are not regarded as Same chunk")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("PREFER")
+                .long("prefer")
+                .help("Chunk to prefer when both of Delete and Insert chunks can be next chunk")
+                .possible_values(&["delete", "insert"])
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("MAX_LOOKAHEAD")
+                .long("max-lookahead")
+                .help("Windows wider than this value are shrunk to this value")
+                .takes_value(true),
+        )
         .get_matches();
 
+    let format = match matches.value_of("FORMAT") {
+        Some("json") => Format::Json,
+        Some("jsonl") => Format::Jsonl,
+        Some("csv") => Format::Csv,
+        _ => Format::Text,
+    };
+
+    if let Some(matches) = matches.subcommand_matches("nearest") {
+        nearest(matches, format);
+        return;
+    }
+    if let Some(matches) = matches.subcommand_matches("minimize") {
+        minimize(matches, format);
+        return;
+    }
+
     let diff = match (matches.value_of("FILE1"), matches.value_of("FILE2")) {
         (Some(file_path_1), Some(file_path_2)) => {
-            let (file_1, file_2) = match (
-                std::fs::File::open(file_path_1),
-                std::fs::File::open(file_path_2),
-            ) {
-                (Ok(file_1), Ok(file_2)) => (file_1, file_2),
-                (Err(why), Ok(_)) => {
-                    eprintln!("[!] File {} does not exist: {:?}", file_path_1, why);
+            if file_path_1 == "-" && file_path_2 == "-" {
+                eprintln!("[!] Both of files cannot be stdin");
+                return;
+            }
+            let (mut file_1, mut file_2) =
+                match (Input::open(file_path_1), Input::open(file_path_2)) {
+                    (Ok(file_1), Ok(file_2)) => (file_1, file_2),
+                    (Err(why), Ok(_)) => {
+                        eprintln!("[!] File {} does not exist: {:?}", file_path_1, why);
+                        return;
+                    }
+                    (Ok(_), Err(why)) => {
+                        eprintln!("[!] File {} does not exist: {:?}", file_path_2, why);
+                        return;
+                    }
+                    (Err(why_1), Err(why_2)) => {
+                        eprintln!("[!] Both of files does not exist");
+                        eprintln!("\t{} does not exist: {:?}", file_path_1, why_1);
+                        eprintln!("\t{} does not exist: {:?}", file_path_2, why_2);
+                        return;
+                    }
+                };
+            let options = diff_options(&matches);
+            if file_1.is_seekable() && file_2.is_seekable() {
+                BinaryDiff::new_with_options(
+                    &mut BufReader::new(file_1),
+                    &mut BufReader::new(file_2),
+                    &options,
+                )
+                .unwrap()
+            } else {
+                if [
+                    "OFFSET",
+                    "ORIGINAL_OFFSET",
+                    "PROVENANCE",
+                    "PROVENANCE_MASK",
+                    "MUTATIONS",
+                ]
+                .iter()
+                .any(|name| matches.is_present(name))
+                {
+                    eprintln!(
+                        "[!] --offset, --original-offset, --provenance and --mutations require seekable files"
+                    );
                     return;
                 }
-                (Ok(_), Err(why)) => {
-                    eprintln!("[!] File {} does not exist: {:?}", file_path_2, why);
-                    return;
+                match matches.value_of("ENGINE") {
+                    Some("streaming") | None => (),
+                    Some(engine) => eprintln!(
+                        "[!] Files are not seekable. Uses streaming engine instead of {}",
+                        engine
+                    ),
                 }
-                (Err(why_1), Err(why_2)) => {
-                    eprintln!("[!] Both of files does not exist");
-                    eprintln!("\t{} does not exist: {:?}", file_path_1, why_1);
-                    eprintln!("\t{} does not exist: {:?}", file_path_2, why_2);
-                    return;
-                }
-            };
-            BinaryDiff::new(&mut BufReader::new(file_1), &mut BufReader::new(file_2)).unwrap()
+                BinaryDiff::new_streaming(file_1, file_2, &options).unwrap()
+            }
         }
         _ => {
             panic!("[!] Parameter FILE1 or FILE2 is not specified");
@@ -75,20 +1246,108 @@ fn main() {
 
         let enhanced_diff = diff.enhance();
         let mut analyzer = BinaryDiffAnalyzer::new(&enhanced_diff, &patched_file);
-        match analyzer.derives_from(offset).unwrap() {
-            Some(derives_from) => println!("{}", derives_from.chunk()),
-            None => eprintln!("[!] offset={:#x} does not derive from no chunks", offset),
+        let derives_from = analyzer.derives_from(offset).unwrap();
+        match (&derives_from, format) {
+            (Some(derives_from), Format::Text) => println!("{}", derives_from.chunk()),
+            (None, Format::Text) => {
+                eprintln!("[!] offset={:#x} does not derive from no chunks", offset)
+            }
+            _ => print_offset_record(
+                &OffsetRecord {
+                    offset,
+                    original_position: derives_from
+                        .as_ref()
+                        .and_then(|derives_from| derives_from.original_position()),
+                    chunk: derives_from.as_ref().map(|derives_from| {
+                        ChunkRecord::new(
+                            derives_from.chunk(),
+                            derives_from.patched_position() - derives_from.relative_position(),
+                        )
+                    }),
+                },
+                format,
+            ),
         }
-    } else {
-        let print_same_chunks = matches.is_present("same");
-        for chunk in diff.enhance().chunks() {
-            let print = match chunk {
-                BinaryDiffChunk::Same(_, _) => print_same_chunks,
-                _ => true,
-            };
-            if print {
-                println!("{}", chunk);
+    } else if matches.is_present("ORIGINAL_OFFSET") {
+        let original_offset =
+            usize::from_str_radix(matches.value_of("ORIGINAL_OFFSET").unwrap(), 16).unwrap();
+        let patched_file =
+            std::fs::File::open(Path::new(matches.value_of("FILE2").unwrap())).unwrap();
+
+        let enhanced_diff = diff.enhance();
+        let analyzer = BinaryDiffAnalyzer::new(&enhanced_diff, &patched_file);
+        let maps_to = analyzer.maps_to(original_offset);
+        match (&maps_to, format) {
+            (Some(maps_to), Format::Text) => {
+                println!("{}", maps_to.chunk().chunk());
+                for position in maps_to.patched_positions().iter() {
+                    println!("-> offset={:#x}", position);
+                }
             }
+            (None, Format::Text) => eprintln!(
+                "[!] original_offset={:#x} is out of original file",
+                original_offset
+            ),
+            _ => print_original_offset_record(
+                &OriginalOffsetRecord {
+                    original_offset,
+                    patched_offsets: maps_to
+                        .as_ref()
+                        .map_or_else(Vec::new, |maps_to| maps_to.patched_positions().to_vec()),
+                    removed: maps_to
+                        .as_ref()
+                        .is_some_and(|maps_to| maps_to.removed_by().is_some()),
+                    chunk: maps_to.as_ref().map(|maps_to| {
+                        ChunkRecord::new(
+                            maps_to.chunk().chunk(),
+                            maps_to.chunk().patched_range().start,
+                        )
+                    }),
+                },
+                format,
+            ),
         }
+    } else if matches.is_present("PROVENANCE") || matches.is_present("PROVENANCE_MASK") {
+        let patched_file =
+            std::fs::File::open(Path::new(matches.value_of("FILE2").unwrap())).unwrap();
+
+        let enhanced_diff = diff.enhance();
+        let mut analyzer = BinaryDiffAnalyzer::new(&enhanced_diff, &patched_file);
+        let runs = analyzer.provenance().unwrap();
+        if let Some(path) = matches.value_of("PROVENANCE_MASK") {
+            if let Err(why) = write_provenance_mask(&runs, path) {
+                eprintln!("[!] Failed to write provenance mask to {}: {:?}", path, why);
+                return;
+            }
+        }
+        if matches.is_present("PROVENANCE") {
+            print_provenance(&runs, format);
+        }
+    } else if matches.is_present("MUTATIONS") {
+        let original_file =
+            std::fs::File::open(Path::new(matches.value_of("FILE1").unwrap())).unwrap();
+
+        let enhanced_diff = diff.enhance();
+        let classifier = MutationClassifier::new(&enhanced_diff, original_file).unwrap();
+        print_mutations(&classifier.classify(), format);
+    } else if let Some(dir) = matches.value_of("CORPUS") {
+        let excluded: Vec<PathBuf> = ["FILE1", "FILE2"]
+            .iter()
+            .filter_map(|name| Path::new(matches.value_of(name)?).canonicalize().ok())
+            .collect();
+        let (corpus, paths) = match load_corpus(dir, &excluded) {
+            Ok(corpus) => corpus,
+            Err(why) => {
+                eprintln!("[!] Failed to read corpus {}: {:?}", dir, why);
+                return;
+            }
+        };
+
+        let enhanced_diff = diff.enhance();
+        print_splice_sources(&corpus.find_splice_sources(&enhanced_diff), &paths, format);
+    } else if matches.is_present("STAT") {
+        print_stats(&diff.enhance().stats(), format);
+    } else {
+        print_chunks(&diff.enhance(), format, matches.is_present("same"));
     }
 }
@@ -0,0 +1,67 @@
+use super::nearest_entry::NearestEntry;
+use super::sketch::Sketch;
+use crate::binary_diff::result::Result;
+use crate::BinaryDiff;
+
+// MinHash sketches of corpus entries to find nearest entries without keeping the entries in memory
+#[derive(Debug, Default)]
+pub struct CorpusSketches {
+    sketches: Vec<Sketch>,
+}
+
+impl CorpusSketches {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Returns index of the added entry. `bytes` is not kept
+    pub fn add(&mut self, bytes: &[u8]) -> usize {
+        self.sketches.push(Sketch::new(bytes));
+        self.sketches.len() - 1
+    }
+
+    pub fn len(&self) -> usize {
+        self.sketches.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sketches.is_empty()
+    }
+
+    // Ranks entries that `bytes` likely derives from in ascending order of edit cost.
+    // Only `candidates` entries of the highest similarity are loaded by `load` and diffed.
+    pub fn nearest<B, F>(
+        &self,
+        bytes: &[u8],
+        candidates: usize,
+        mut load: F,
+    ) -> Result<Vec<NearestEntry>>
+    where
+        B: AsRef<[u8]>,
+        F: FnMut(usize) -> Result<B>,
+    {
+        let sketch = Sketch::new(bytes);
+        let mut similarities: Vec<(usize, f64)> = self
+            .sketches
+            .iter()
+            .map(|other| sketch.similarity(other))
+            .enumerate()
+            .collect();
+        similarities
+            .sort_by(|(_, a), (_, b)| b.partial_cmp(a).unwrap_or(std::cmp::Ordering::Equal));
+
+        let mut nearest = vec![];
+        for (entry, similarity) in similarities.into_iter().take(candidates) {
+            let diff = BinaryDiff::from_slices(load(entry)?.as_ref(), bytes)?;
+            let edit_cost = diff.stats().edit_distance();
+            nearest.push(NearestEntry {
+                entry,
+                similarity,
+                edit_cost,
+                diff,
+            });
+        }
+        nearest.sort_by_key(|nearest| nearest.edit_cost);
+        Ok(nearest)
+    }
+}
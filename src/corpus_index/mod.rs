@@ -0,0 +1,258 @@
+use crate::binary_diff::result::Result;
+use crate::{BinaryDiff, BinaryDiffChunk};
+use corpus_sketches::CorpusSketches;
+use nearest_entry::NearestEntry;
+use splice_source::SpliceSource;
+use std::collections::HashMap;
+use std::convert::TryInto;
+use std::ops::Range;
+
+mod sketch;
+
+pub(super) mod corpus_sketches;
+pub(super) mod nearest_entry;
+pub(super) mod splice_source;
+
+// Length of k-grams to be indexed. Copied bytes shorter than this value are not found
+const KGRAM_LENGTH: usize = 8;
+// K-grams at offsets of multiples of this value are indexed to bound memory usage.
+// Copied bytes of at least KGRAM_LENGTH + KGRAM_STEP - 1 bytes are always found.
+const KGRAM_STEP: usize = 4;
+// Occurrences of frequent k-grams (e.g. runs of zeros) examined at most
+const MAX_CANDIDATES: usize = 0x40;
+
+// Index of corpus entries (e.g. queue of AFL) to find where bytes of Insert() and Replace() chunks are copied from
+#[derive(Debug, Default)]
+pub struct CorpusIndex {
+    entries: Vec<Vec<u8>>,
+    sketches: CorpusSketches,
+    // (index of entry, offset in entry) of each k-gram
+    kgrams: HashMap<u64, Vec<(usize, usize)>>,
+}
+
+fn kgram(bytes: &[u8]) -> u64 {
+    u64::from_le_bytes(bytes[..KGRAM_LENGTH].try_into().unwrap())
+}
+
+fn common_prefix_length<'a, I: Iterator<Item = &'a u8>>(a: I, b: I) -> usize {
+    a.zip(b).take_while(|(a, b)| a == b).count()
+}
+
+impl CorpusIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Returns index of the added entry
+    pub fn add(&mut self, bytes: Vec<u8>) -> usize {
+        let entry = self.entries.len();
+        if bytes.len() >= KGRAM_LENGTH {
+            for offset in (0..=bytes.len() - KGRAM_LENGTH).step_by(KGRAM_STEP) {
+                self.kgrams
+                    .entry(kgram(&bytes[offset..]))
+                    .or_default()
+                    .push((entry, offset));
+            }
+        }
+        self.sketches.add(&bytes);
+        self.entries.push(bytes);
+        entry
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn entry(&self, entry: usize) -> Option<&[u8]> {
+        self.entries.get(entry).map(|bytes| bytes.as_slice())
+    }
+
+    // Finds sources of payloads of Insert() and Replace() chunks in ascending order of offset
+    pub fn find_splice_sources<'a>(&self, diff: &'a BinaryDiff) -> Vec<SpliceSource<'a>> {
+        let mut sources = vec![];
+        for positioned in diff.iter_with_positions() {
+            let payload = match positioned.chunk() {
+                BinaryDiffChunk::Insert(_, bytes) | BinaryDiffChunk::Replace(_, _, bytes) => bytes,
+                _ => continue,
+            };
+            for (payload_range, entry, entry_offset) in self.find_matches(payload).into_iter() {
+                sources.push(SpliceSource {
+                    chunk: positioned.clone(),
+                    payload_range,
+                    entry,
+                    entry_offset,
+                });
+            }
+        }
+        sources
+    }
+
+    // Ranks entries that `bytes` likely derives from in ascending order of edit cost.
+    // Only `candidates` entries of the highest similarity are diffed since diff is expensive.
+    // See CorpusSketches::nearest() not to keep entries in memory.
+    pub fn nearest(&self, bytes: &[u8], candidates: usize) -> Result<Vec<NearestEntry>> {
+        self.sketches.nearest(
+            bytes,
+            candidates,
+            |entry| Ok(self.entries[entry].as_slice()),
+        )
+    }
+
+    // Finds non-overlapping matches of at least KGRAM_LENGTH bytes from the start of `payload`.
+    // Returns (range in payload, index of entry, offset in entry) of each match.
+    fn find_matches(&self, payload: &[u8]) -> Vec<(Range<usize>, usize, usize)> {
+        let mut matches = vec![];
+        // End of the last match. Matches are not extended backward beyond it
+        let mut lower = 0;
+        let mut position = 0;
+        while position + KGRAM_LENGTH <= payload.len() {
+            let candidates = match self.kgrams.get(&kgram(&payload[position..])) {
+                Some(candidates) => candidates,
+                None => {
+                    position += 1;
+                    continue;
+                }
+            };
+            // (start in payload, length, index of entry, offset in entry)
+            let best = candidates
+                .iter()
+                .take(MAX_CANDIDATES)
+                .map(|(entry, offset)| {
+                    let bytes = &self.entries[*entry];
+                    let backward = common_prefix_length(
+                        payload[lower..position].iter().rev(),
+                        bytes[..*offset].iter().rev(),
+                    );
+                    let forward =
+                        common_prefix_length(payload[position..].iter(), bytes[*offset..].iter());
+                    (
+                        position - backward,
+                        backward + forward,
+                        *entry,
+                        offset - backward,
+                    )
+                })
+                .max_by_key(|(start, length, entry, _)| {
+                    (*length, std::cmp::Reverse((*entry, *start)))
+                });
+            match best {
+                Some((start, length, entry, offset)) if length >= KGRAM_LENGTH => {
+                    matches.push((start..start + length, entry, offset));
+                    lower = start + length;
+                    position = lower;
+                }
+                _ => position += 1,
+            }
+        }
+        matches
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CorpusIndex, CorpusSketches};
+    use crate::binary_diff::binary_diff_chunk::BinaryDiffChunk::{Insert, Replace, Same};
+    use crate::binary_diff::result::Result;
+    use crate::test_helper::random_bytes;
+    use crate::BinaryDiff;
+    use crate::BinaryDiffError;
+
+    #[test]
+    fn test_find_splice_sources() {
+        let mut seed = 0x1234_5678;
+        let mut corpus = CorpusIndex::new();
+        let entries: Vec<Vec<u8>> = (0..3).map(|_| random_bytes(&mut seed, 0x100)).collect();
+        for entry in entries.iter() {
+            corpus.add(entry.clone());
+        }
+        assert_eq!(corpus.len(), 3);
+
+        let noise = random_bytes(&mut seed, 5);
+        let payload = [&noise, &entries[1][0x23..0x40], &entries[2][0x80..0x8b]].concat();
+        let diff = BinaryDiff::from(&vec![
+            Same(0, 4),
+            Insert(4, payload),
+            Same(4, 4),
+            Replace(8, 2, noise.clone()),
+        ]);
+        let sources: Vec<_> = corpus
+            .find_splice_sources(&diff)
+            .iter()
+            .map(|source| {
+                (
+                    source.payload_range(),
+                    source.patched_range(),
+                    source.entry(),
+                    source.entry_range(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            sources,
+            vec![
+                (5..0x22, 9..0x26, 1, 0x23..0x40),
+                (0x22..0x2d, 0x26..0x31, 2, 0x80..0x8b),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_splice_sources_short_payload() {
+        let mut corpus = CorpusIndex::new();
+        corpus.add(b"0123456789abcdef".to_vec());
+        corpus.add(vec![]);
+        let diff = BinaryDiff::from(&vec![Insert(0, b"3456789".to_vec())]);
+        assert_eq!(corpus.find_splice_sources(&diff), vec![]);
+    }
+
+    #[test]
+    fn test_nearest() {
+        let mut seed = 0x1234_5678;
+        let mut corpus = CorpusIndex::new();
+        let entries: Vec<Vec<u8>> = (0..8).map(|_| random_bytes(&mut seed, 0x100)).collect();
+        for entry in entries.iter() {
+            corpus.add(entry.clone());
+        }
+
+        let mut crash = entries[5].clone();
+        crash[0x10] = !crash[0x10];
+        crash.splice(0x80..0x80, random_bytes(&mut seed, 4));
+        let nearest = corpus.nearest(&crash, 3).unwrap();
+        assert_eq!(nearest.len(), 3);
+        assert_eq!(nearest[0].entry(), 5);
+        assert_eq!(nearest[0].edit_cost(), 6);
+        assert!(nearest[0].similarity() > nearest[1].similarity());
+
+        let mut patched = vec![];
+        nearest[0]
+            .diff()
+            .apply(std::io::Cursor::new(&entries[5]), &mut patched)
+            .unwrap();
+        assert_eq!(patched, crash);
+
+        let mut sketches = CorpusSketches::new();
+        for entry in entries.iter() {
+            sketches.add(entry);
+        }
+        let mut loaded = vec![];
+        let nearest_by_sketches = sketches
+            .nearest(&crash, 3, |entry| {
+                loaded.push(entry);
+                Ok(entries[entry].clone())
+            })
+            .unwrap();
+        assert_eq!(nearest_by_sketches, nearest);
+        assert_eq!(loaded.len(), 3);
+        assert!(sketches
+            .nearest(&crash, 3, |_| -> Result<Vec<u8>> {
+                Err(BinaryDiffError::IoError(
+                    std::io::ErrorKind::NotFound.into(),
+                ))
+            })
+            .is_err());
+    }
+}
@@ -0,0 +1,32 @@
+use crate::BinaryDiff;
+
+// Corpus entry that a binary likely derives from
+#[derive(Debug, PartialEq)]
+pub struct NearestEntry {
+    pub(crate) entry: usize,
+    pub(crate) similarity: f64,
+    pub(crate) edit_cost: usize,
+    // Diff from the entry to the binary
+    pub(crate) diff: BinaryDiff,
+}
+
+impl NearestEntry {
+    // Index of the entry given by CorpusIndex::add()
+    pub fn entry(&self) -> usize {
+        self.entry
+    }
+
+    // Estimated similarity of n-grams used to prefilter entries
+    pub fn similarity(&self) -> f64 {
+        self.similarity
+    }
+
+    // Total bytes removed from the entry and introduced to the binary
+    pub fn edit_cost(&self) -> usize {
+        self.edit_cost
+    }
+
+    pub fn diff(&self) -> &BinaryDiff {
+        &self.diff
+    }
+}
@@ -0,0 +1,77 @@
+// Length of n-grams to be sketched
+const NGRAM_LENGTH: usize = 4;
+// Number of buckets of one permutation MinHash
+const BUCKETS: usize = 0x40;
+
+// MinHash sketch of n-grams to estimate Jaccard similarity of two binaries cheaply
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub(super) struct Sketch {
+    // Minimum hash of n-grams in each bucket. None if no n-grams fall into the bucket
+    minimums: Vec<Option<u64>>,
+}
+
+// SplitMix64 finalizer
+fn mix(value: u64) -> u64 {
+    let value = (value ^ (value >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    let value = (value ^ (value >> 27)).wrapping_mul(0x94d049bb133111eb);
+    value ^ (value >> 31)
+}
+
+impl Sketch {
+    pub(super) fn new(bytes: &[u8]) -> Self {
+        let mut minimums = vec![None; BUCKETS];
+        // NOTE: Binary shorter than NGRAM_LENGTH is regarded as one n-gram
+        for ngram in bytes.windows(std::cmp::min(NGRAM_LENGTH, bytes.len()).max(1)) {
+            let hash = mix(ngram
+                .iter()
+                .fold(ngram.len() as u64, |hash, byte| (hash << 8) | *byte as u64));
+            let bucket = &mut minimums[hash as usize % BUCKETS];
+            if bucket.map_or(true, |minimum| hash < minimum) {
+                *bucket = Some(hash);
+            }
+        }
+        Self { minimums }
+    }
+
+    // Estimated Jaccard similarity of n-grams in 0.0..=1.0
+    pub(super) fn similarity(&self, other: &Self) -> f64 {
+        let (mut same, mut total) = (0, 0);
+        for (a, b) in self.minimums.iter().zip(other.minimums.iter()) {
+            match (a, b) {
+                (None, None) => (),
+                (a, b) => {
+                    total += 1;
+                    if a == b {
+                        same += 1;
+                    }
+                }
+            }
+        }
+        if total == 0 {
+            1.0
+        } else {
+            same as f64 / total as f64
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Sketch;
+    use crate::test_helper::random_bytes;
+
+    #[test]
+    fn test_sketch_similarity() {
+        let mut seed = 0x1234_5678;
+        let a = random_bytes(&mut seed, 0x400);
+        let mut b = a.clone();
+        b[0x100..0x140].copy_from_slice(&random_bytes(&mut seed, 0x40));
+        let c = [&a[0..0x100], &random_bytes(&mut seed, 0x300)].concat();
+
+        let sketch = Sketch::new(&a);
+        assert_eq!(sketch.similarity(&Sketch::new(&a)), 1.0);
+        assert!(sketch.similarity(&Sketch::new(&b)) > sketch.similarity(&Sketch::new(&c)));
+        assert_eq!(Sketch::new(&[]).similarity(&Sketch::new(&[])), 1.0);
+        assert_eq!(Sketch::new(&[1]).similarity(&Sketch::new(&[])), 0.0);
+    }
+}
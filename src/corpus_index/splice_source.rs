@@ -0,0 +1,38 @@
+use crate::PositionedChunk;
+use std::ops::Range;
+
+// Bytes of payload of Insert() or Replace() chunk that are copied from an entry of corpus
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SpliceSource<'a> {
+    pub(crate) chunk: PositionedChunk<'a>,
+    // Range in payload of the chunk
+    pub(crate) payload_range: Range<usize>,
+    pub(crate) entry: usize,
+    pub(crate) entry_offset: usize,
+}
+
+impl<'a> SpliceSource<'a> {
+    pub fn chunk(&self) -> &PositionedChunk<'a> {
+        &self.chunk
+    }
+
+    pub fn payload_range(&self) -> Range<usize> {
+        self.payload_range.clone()
+    }
+
+    // Range of patched binary of the copied bytes
+    pub fn patched_range(&self) -> Range<usize> {
+        let start = self.chunk.patched_range().start;
+        start + self.payload_range.start..start + self.payload_range.end
+    }
+
+    // Index of the entry given by CorpusIndex::add()
+    pub fn entry(&self) -> usize {
+        self.entry
+    }
+
+    // Range of the entry that the bytes are copied from
+    pub fn entry_range(&self) -> Range<usize> {
+        self.entry_offset..self.entry_offset + self.payload_range.len()
+    }
+}
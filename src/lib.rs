@@ -3,12 +3,39 @@
 
 mod binary_diff;
 mod binary_diff_analyzer;
+mod corpus_index;
+mod lineage;
+mod minimizer;
+mod mutation_classifier;
+#[cfg(test)]
+mod test_helper;
 
 // Exported objects
 pub use crate::binary_diff::binary_diff_chunk::BinaryDiffChunk;
+pub use crate::binary_diff::conflict_resolution::ConflictResolution;
+pub use crate::binary_diff::diff_algorithm::{
+    DiffAlgorithm, GreedyAlgorithm, MyersAlgorithm, RollingHashAlgorithm, StreamingAlgorithm,
+    SuffixArrayAlgorithm,
+};
+pub use crate::binary_diff::diff_options::{DiffEngine, DiffOptions, TieBreaking};
+pub use crate::binary_diff::diff_stats::DiffStats;
+pub use crate::binary_diff::error::BinaryDiffError;
+pub use crate::binary_diff::positioned_chunk::PositionedChunk;
 pub use crate::binary_diff::BinaryDiff;
-pub use crate::binary_diff_analyzer::derives_from::DerivesFrom;
+pub use crate::binary_diff_analyzer::derives_from::{DerivesFrom, DerivesFromRange};
+pub use crate::binary_diff_analyzer::maps_to::MapsTo;
+pub use crate::binary_diff_analyzer::provenance::{Provenance, ProvenanceRun};
 pub use crate::binary_diff_analyzer::BinaryDiffAnalyzer;
+pub use crate::corpus_index::corpus_sketches::CorpusSketches;
+pub use crate::corpus_index::nearest_entry::NearestEntry;
+pub use crate::corpus_index::splice_source::SpliceSource;
+pub use crate::corpus_index::CorpusIndex;
+pub use crate::lineage::trace::{LineageHop, LineageTrace};
+pub use crate::lineage::Lineage;
+pub use crate::minimizer::minimized::Minimized;
+pub use crate::minimizer::Minimizer;
+pub use crate::mutation_classifier::mutation::{Endian, Mutation, MutationLabel};
+pub use crate::mutation_classifier::MutationClassifier;
 
 // extern crate alloc;
 extern crate bcmp;
@@ -0,0 +1,147 @@
+use crate::binary_diff::result::Result;
+use crate::binary_diff_analyzer::patched_index::PatchedIndex;
+use crate::{BinaryDiff, BinaryDiffChunk, DerivesFrom, DiffOptions};
+use std::io::{BufReader, Read, Seek};
+use trace::{LineageHop, LineageTrace};
+
+pub(super) mod trace;
+
+// Chain of files where each file is derived from the previous one, e.g. seed and its mutants
+#[derive(Debug, Eq, PartialEq)]
+pub struct Lineage {
+    // diffs[i] is diff from generation i to generation i + 1
+    diffs: Vec<BinaryDiff>,
+}
+
+impl Lineage {
+    pub fn new<R: Read + Seek>(files: &mut [BufReader<R>]) -> Result<Self> {
+        Self::new_with_options(files, &DiffOptions::new())
+    }
+
+    pub fn new_with_options<R: Read + Seek>(
+        files: &mut [BufReader<R>],
+        options: &DiffOptions,
+    ) -> Result<Self> {
+        let mut diffs = vec![];
+        for i in 1..files.len() {
+            let (old, new) = files.split_at_mut(i);
+            diffs.push(BinaryDiff::new_with_options(
+                &mut old[i - 1],
+                &mut new[0],
+                options,
+            )?);
+        }
+        Ok(Self::from_diffs(diffs))
+    }
+
+    pub fn from_slices(files: &[&[u8]]) -> Result<Self> {
+        let mut diffs = vec![];
+        for pair in files.windows(2) {
+            diffs.push(BinaryDiff::from_slices(pair[0], pair[1])?);
+        }
+        Ok(Self::from_diffs(diffs))
+    }
+
+    // `diffs` must be of consecutive generations
+    pub fn from_diffs(diffs: Vec<BinaryDiff>) -> Self {
+        Self { diffs }
+    }
+
+    pub fn generations(&self) -> usize {
+        self.diffs.len() + 1
+    }
+
+    pub fn diffs(&self) -> &[BinaryDiff] {
+        &self.diffs
+    }
+
+    // Traces `offset` of the last generation back to the first generation.
+    // Returns None if `offset` derives from no chunks, or there is only one generation.
+    pub fn trace(&self, offset: usize) -> Option<LineageTrace<'_>> {
+        let mut hops = vec![];
+        let mut position = offset;
+        for (i, diff) in self.diffs.iter().enumerate().rev() {
+            let index = PatchedIndex::new(diff);
+            let positioned = index.find(position)?;
+            let derives_from = DerivesFrom {
+                patched_position: position,
+                relative_position: position - positioned.patched_range().start,
+                chunk: positioned.chunk(),
+            };
+            let original_position = derives_from.original_position();
+            hops.push(LineageHop {
+                generation: i + 1,
+                derives_from,
+            });
+            match (positioned.chunk(), original_position) {
+                (BinaryDiffChunk::Same(_, _), Some(original_position))
+                | (BinaryDiffChunk::Copy(_, _, _), Some(original_position)) => {
+                    position = original_position
+                }
+                // NOTE: Diffs are of the files themselves, so bytes of Insert() and Replace() chunks match
+                _ => break,
+            }
+        }
+        if hops.is_empty() {
+            None
+        } else {
+            Some(LineageTrace { hops })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::binary_diff::binary_diff_chunk::BinaryDiffChunk::{Insert, Same};
+    use crate::Lineage;
+    use std::io::{BufReader, Cursor};
+
+    #[test]
+    fn test_lineage_trace() {
+        let seed = b"0123456789abcdef".to_vec();
+        let child = b"0123456789XYZabcdef".to_vec();
+        let grandchild = b"ab0123456789XYZcdef".to_vec();
+        let lineage = Lineage::from_slices(&[&seed, &child, &grandchild]).unwrap();
+        assert_eq!(lineage.generations(), 3);
+
+        // Byte from seed
+        let trace = lineage.trace(2).unwrap();
+        assert_eq!(trace.introduced_in(), 0);
+        assert_eq!(trace.origin_position(), Some(0));
+        assert_eq!(
+            trace
+                .hops()
+                .iter()
+                .map(|hop| (hop.generation(), hop.derives_from().patched_position()))
+                .collect::<Vec<_>>(),
+            vec![(2, 2), (1, 0)]
+        );
+
+        // Byte inserted by child
+        let trace = lineage.trace(13).unwrap();
+        assert_eq!(trace.introduced_in(), 1);
+        assert_eq!(trace.origin_position(), None);
+        assert_eq!(
+            trace.hops().last().unwrap().derives_from().chunk(),
+            &Insert(10, b"XYZ".to_vec())
+        );
+
+        // Byte inserted by grandchild
+        let trace = lineage.trace(0).unwrap();
+        assert_eq!(trace.introduced_in(), 2);
+        assert_eq!(trace.hops().len(), 1);
+
+        assert_eq!(lineage.trace(19), None);
+    }
+
+    #[test]
+    fn test_lineage_new() {
+        let mut files: Vec<_> = [b"0123".to_vec(), b"0123".to_vec()]
+            .iter()
+            .map(|file| BufReader::new(Cursor::new(file.clone())))
+            .collect();
+        let lineage = Lineage::new(&mut files).unwrap();
+        assert_eq!(lineage.diffs()[0].chunks(), &vec![Same(0, 4)]);
+        assert_eq!(Lineage::from_slices(&[b"0123"]).unwrap().trace(0), None);
+    }
+}
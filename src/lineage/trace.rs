@@ -0,0 +1,47 @@
+use crate::{BinaryDiffChunk, DerivesFrom};
+
+// A byte of generation `generation` derives from `derives_from` chunk of diff from the previous generation
+#[derive(Debug, Eq, PartialEq)]
+pub struct LineageHop<'a> {
+    pub(crate) generation: usize,
+    pub(crate) derives_from: DerivesFrom<'a>,
+}
+
+impl<'a> LineageHop<'a> {
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    pub fn derives_from(&self) -> &DerivesFrom<'a> {
+        &self.derives_from
+    }
+}
+
+// Hops of a byte of the last generation back to the first generation, or to the generation that introduced it
+#[derive(Debug, Eq, PartialEq)]
+pub struct LineageTrace<'a> {
+    // In descending order of generation
+    pub(crate) hops: Vec<LineageHop<'a>>,
+}
+
+impl<'a> LineageTrace<'a> {
+    pub fn hops(&self) -> &[LineageHop<'a>] {
+        &self.hops
+    }
+
+    // Generation that introduced the byte by Insert() or Replace() chunk. 0 if the byte is from the first generation
+    pub fn introduced_in(&self) -> usize {
+        match self.hops.last() {
+            Some(hop) => match hop.derives_from.chunk() {
+                BinaryDiffChunk::Insert(_, _) | BinaryDiffChunk::Replace(_, _, _) => hop.generation,
+                _ => 0,
+            },
+            None => 0,
+        }
+    }
+
+    // Offset of the byte in the first generation if the byte is from the first generation
+    pub fn origin_position(&self) -> Option<usize> {
+        self.hops.last()?.derives_from.original_position()
+    }
+}
@@ -0,0 +1,25 @@
+use crate::BinaryDiff;
+
+// Result of Minimizer::minimize()
+#[derive(Debug, Eq, PartialEq)]
+pub struct Minimized {
+    // Diff from original binary to `bytes`
+    pub(crate) diff: BinaryDiff,
+    pub(crate) bytes: Vec<u8>,
+    // Number of times that the oracle is called
+    pub(crate) tests: usize,
+}
+
+impl Minimized {
+    pub fn diff(&self) -> &BinaryDiff {
+        &self.diff
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub fn tests(&self) -> usize {
+        self.tests
+    }
+}
@@ -0,0 +1,172 @@
+use crate::binary_diff::result::Result;
+use crate::{BinaryDiff, BinaryDiffChunk};
+use minimized::Minimized;
+use std::collections::HashMap;
+use std::io::Cursor;
+
+pub(super) mod minimized;
+
+// Reduces a crashing binary towards its original binary by applying subsets of changes of the diff (ddmin)
+pub struct Minimizer<F: FnMut(&[u8]) -> bool> {
+    original: Vec<u8>,
+    diff: BinaryDiff,
+    // Returns true if the binary still reproduces the crash
+    oracle: F,
+    // Results of the oracle for each subset of changes
+    cache: HashMap<Vec<usize>, bool>,
+}
+
+impl<F: FnMut(&[u8]) -> bool> Minimizer<F> {
+    pub fn new(original: &[u8], crash: &[u8], oracle: F) -> Result<Self> {
+        Ok(Self::with_diff(
+            original,
+            BinaryDiff::from_slices(original, crash)?.enhance(),
+            oracle,
+        ))
+    }
+
+    // `diff` must be from `original` to a crashing binary
+    pub fn with_diff(original: &[u8], diff: BinaryDiff, oracle: F) -> Self {
+        Self {
+            original: original.to_vec(),
+            diff,
+            oracle,
+            cache: HashMap::new(),
+        }
+    }
+
+    // Returns None if the crashing binary itself does not reproduce the crash
+    pub fn minimize(mut self) -> Result<Option<Minimized>> {
+        // Indices of chunks other than Same() chunks
+        let mut changes: Vec<usize> = self
+            .diff
+            .chunks()
+            .iter()
+            .enumerate()
+            .filter(|(_, chunk)| !matches!(chunk, BinaryDiffChunk::Same(_, _)))
+            .map(|(index, _)| index)
+            .collect();
+        if !self.test(&changes)? {
+            return Ok(None);
+        }
+        if self.test(&[])? {
+            changes.clear();
+        }
+
+        let mut granularity = 2;
+        while changes.len() >= 2 {
+            let size = changes.len().div_ceil(granularity);
+            let subsets: Vec<Vec<usize>> =
+                changes.chunks(size).map(|subset| subset.to_vec()).collect();
+            let complements: Vec<Vec<usize>> = (0..subsets.len())
+                .map(|i| {
+                    changes
+                        .iter()
+                        .enumerate()
+                        .filter(|(j, _)| j / size != i)
+                        .map(|(_, change)| *change)
+                        .collect()
+                })
+                .collect();
+
+            let mut reduced = None;
+            for subset in subsets.iter() {
+                if self.test(subset)? {
+                    reduced = Some((subset.clone(), 2));
+                    break;
+                }
+            }
+            if reduced.is_none() && subsets.len() > 2 {
+                for complement in complements.iter() {
+                    if self.test(complement)? {
+                        reduced = Some((complement.clone(), std::cmp::max(granularity - 1, 2)));
+                        break;
+                    }
+                }
+            }
+            match reduced {
+                Some((subset, next_granularity)) => {
+                    log::trace!("Reduced: changes = {:?}", subset);
+                    changes = subset;
+                    granularity = next_granularity;
+                }
+                None if granularity < changes.len() => {
+                    granularity = std::cmp::min(granularity * 2, changes.len())
+                }
+                None => break,
+            }
+        }
+
+        let diff = self.diff.select_changes(&changes);
+        let bytes = self.apply(&diff)?;
+        Ok(Some(Minimized {
+            diff,
+            bytes,
+            tests: self.cache.len(),
+        }))
+    }
+
+    fn apply(&self, diff: &BinaryDiff) -> Result<Vec<u8>> {
+        let mut bytes = vec![];
+        diff.apply(Cursor::new(&self.original), &mut bytes)?;
+        Ok(bytes)
+    }
+
+    fn test(&mut self, changes: &[usize]) -> Result<bool> {
+        if let Some(result) = self.cache.get(changes) {
+            return Ok(*result);
+        }
+        let bytes = self.apply(&self.diff.select_changes(changes))?;
+        let result = (self.oracle)(&bytes);
+        self.cache.insert(changes.to_vec(), result);
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Minimizer;
+    use crate::BinaryDiffChunk::{Replace, Same};
+
+    #[test]
+    fn test_minimize() {
+        let original = b"0123456789abcdefghijklmnopqrstuv".to_vec();
+        let mut crash = original.clone();
+        for offset in [2, 7, 12, 17, 22, 27].iter() {
+            crash[*offset] = b'!';
+        }
+        // Crashes if offset 7 and 22 are mutated
+        let oracle = |bytes: &[u8]| bytes.len() > 22 && bytes[7] == b'!' && bytes[22] == b'!';
+
+        let minimized = Minimizer::new(&original, &crash, oracle)
+            .unwrap()
+            .minimize()
+            .unwrap()
+            .unwrap();
+        let mut expected = original.clone();
+        expected[7] = b'!';
+        expected[22] = b'!';
+        assert_eq!(minimized.bytes(), expected.as_slice());
+        assert_eq!(
+            minimized.diff().enhance().chunks(),
+            &vec![
+                Same(0, 7),
+                Replace(7, 1, b"!".to_vec()),
+                Same(8, 14),
+                Replace(22, 1, b"!".to_vec()),
+                Same(23, 9)
+            ]
+        );
+        assert!(minimized.tests() < 64);
+    }
+
+    #[test]
+    fn test_minimize_not_reproduced() {
+        let minimizer = Minimizer::new(b"0123", b"0124", |_: &[u8]| false).unwrap();
+        assert_eq!(minimizer.minimize().unwrap(), None);
+
+        let minimizer = Minimizer::new(b"0123", b"0124", |_: &[u8]| true).unwrap();
+        let minimized = minimizer.minimize().unwrap().unwrap();
+        assert_eq!(minimized.bytes(), b"0123");
+    }
+}
@@ -0,0 +1,322 @@
+use crate::binary_diff::error::BinaryDiffError;
+use crate::binary_diff::result::Result;
+use crate::{BinaryDiff, BinaryDiffChunk, PositionedChunk};
+use mutation::{Endian, Mutation, MutationLabel};
+use std::io::{Read, Seek, SeekFrom};
+
+pub(super) mod mutation;
+
+// Deltas of arithmetic mutations of AFL are in -ARITH_MAX..=ARITH_MAX
+const ARITH_MAX: i64 = 35;
+// Shorter blocks are found in original binary by chance
+const MIN_DUPLICATION_LENGTH: usize = 4;
+// Shorter changes at the end of binary are not regarded as splice
+const MIN_SPLICE_LENGTH: usize = 8;
+
+const INTERESTING_8: [i64; 9] = [-128, -1, 0, 1, 16, 32, 64, 100, 127];
+const INTERESTING_16: [i64; 10] = [-32768, -129, 128, 255, 256, 512, 1000, 1024, 4096, 32767];
+const INTERESTING_32: [i64; 8] = [
+    -2147483648,
+    -100663046,
+    -32769,
+    32768,
+    65535,
+    65536,
+    100663045,
+    2147483647,
+];
+
+// Labels changes of BinaryDiff with likely AFL-style mutation operators
+pub struct MutationClassifier<'a> {
+    diff: &'a BinaryDiff,
+    original: Vec<u8>,
+}
+
+impl<'a> MutationClassifier<'a> {
+    pub fn new<R: Read + Seek>(diff: &'a BinaryDiff, mut original: R) -> Result<Self> {
+        let mut bytes = vec![];
+        original
+            .seek(SeekFrom::Start(0))
+            .map_err(BinaryDiffError::IoError)?;
+        original
+            .read_to_end(&mut bytes)
+            .map_err(BinaryDiffError::IoError)?;
+        Ok(Self {
+            diff,
+            original: bytes,
+        })
+    }
+
+    pub fn from_slice(diff: &'a BinaryDiff, original: &[u8]) -> Self {
+        Self {
+            diff,
+            original: original.to_vec(),
+        }
+    }
+
+    // Returns labels in ascending order of offset. Labels of the same change are in descending order of confidence.
+    // NOTE: Call this on BinaryDiff::enhance() to have Replace() chunks. Delete() and Insert() chunks are also labeled
+    pub fn classify(&self) -> Vec<MutationLabel<'a>> {
+        let positioned: Vec<PositionedChunk<'a>> = self.diff.iter_with_positions().collect();
+        let patched_length = positioned
+            .last()
+            .map_or(0, |positioned| positioned.patched_range().end);
+        let is_same = |positioned: &PositionedChunk| {
+            matches!(positioned.chunk(), BinaryDiffChunk::Same(_, _))
+        };
+
+        let mut labels = vec![];
+        let mut start = 0;
+        while start < positioned.len() {
+            if is_same(&positioned[start]) {
+                start += 1;
+                continue;
+            }
+            // Consecutive chunks other than Same() chunks are regarded as one change
+            let end = (start..positioned.len())
+                .find(|i| is_same(&positioned[*i]))
+                .unwrap_or(positioned.len());
+            let group = &positioned[start..end];
+            let original_range = group[0].original_range().start
+                ..group
+                    .iter()
+                    .map(|positioned| positioned.original_range().end)
+                    .max()
+                    .unwrap_or(0);
+            let patched_range =
+                group[0].patched_range().start..group[group.len() - 1].patched_range().end;
+
+            // NOTE: Chunks out of original binary mean that the diff is not of this original binary
+            if let (Some(old), Some(new)) = (
+                self.original.get(original_range.clone()),
+                self.patched_bytes(group),
+            ) {
+                let is_tail = original_range.end == self.original.len()
+                    && patched_range.end == patched_length;
+                for (mutation, confidence) in
+                    classify_change(&self.original, group, old, &new, is_tail)
+                {
+                    labels.push(MutationLabel {
+                        chunks: &self.diff.chunks()[start..end],
+                        original_range: original_range.clone(),
+                        patched_range: patched_range.clone(),
+                        mutation,
+                        confidence,
+                    });
+                }
+            }
+            start = end;
+        }
+        labels
+    }
+
+    fn patched_bytes(&self, group: &[PositionedChunk]) -> Option<Vec<u8>> {
+        let mut bytes = vec![];
+        for positioned in group.iter() {
+            match positioned.chunk() {
+                BinaryDiffChunk::Insert(_, inserted) | BinaryDiffChunk::Replace(_, _, inserted) => {
+                    bytes.extend(inserted)
+                }
+                BinaryDiffChunk::Copy(_, source, length) => {
+                    bytes.extend(self.original.get(*source..source + length)?)
+                }
+                _ => (),
+            }
+        }
+        Some(bytes)
+    }
+}
+
+fn to_integer(bytes: &[u8], endian: Endian) -> u64 {
+    let fold = |value: u64, byte: &u8| (value << 8) | *byte as u64;
+    match endian {
+        Endian::Little => bytes.iter().rev().fold(0, fold),
+        Endian::Big => bytes.iter().fold(0, fold),
+    }
+}
+
+fn sign_extend(value: u64, width: usize) -> i64 {
+    let shift = 64 - width * 8;
+    ((value << shift) as i64) >> shift
+}
+
+fn is_interesting(value: i64, width: usize) -> bool {
+    // NOTE: AFL also tries interesting values of narrower widths
+    INTERESTING_8.contains(&value)
+        || (width >= 2 && INTERESTING_16.contains(&value))
+        || (width >= 4 && INTERESTING_32.contains(&value))
+}
+
+// Returns candidates of mutation that changes `old` into `new`, with confidence
+fn classify_change(
+    original: &[u8],
+    group: &[PositionedChunk],
+    old: &[u8],
+    new: &[u8],
+    is_tail: bool,
+) -> Vec<(Mutation, f64)> {
+    let mut candidates = vec![];
+    match (old.len(), new.len()) {
+        (0, 0) => (),
+        (_, 0) => candidates.push((Mutation::BlockDeletion, 0.9)),
+        (0, _) => {
+            if let [positioned] = group {
+                match positioned.chunk() {
+                    BinaryDiffChunk::Copy(_, source, _) => {
+                        candidates.push((Mutation::BlockDuplication(*source), 0.9))
+                    }
+                    _ if new.len() >= MIN_DUPLICATION_LENGTH => {
+                        if let Some(source) =
+                            original.windows(new.len()).position(|block| block == new)
+                        {
+                            candidates.push((Mutation::BlockDuplication(source), 0.7))
+                        }
+                    }
+                    _ => (),
+                }
+            }
+        }
+        (length, _) if length == new.len() => {
+            let xors: Vec<u8> = old.iter().zip(new.iter()).map(|(a, b)| a ^ b).collect();
+
+            // AFL flips bits from the most significant bit of each byte
+            let flipped: Vec<usize> = (0..length * 8)
+                .filter(|bit| xors[bit / 8] & (0x80 >> (bit % 8)) != 0)
+                .collect();
+            if let (Some(first), Some(last)) = (flipped.first(), flipped.last()) {
+                if [1, 2, 4].contains(&flipped.len()) && last - first + 1 == flipped.len() {
+                    candidates.push((Mutation::BitFlip(flipped.len()), 0.9));
+                }
+            }
+            if [1, 2, 4].contains(&length) && xors.iter().all(|xor| *xor == 0xff) {
+                candidates.push((Mutation::ByteFlip(length), 0.9));
+            }
+
+            if [1, 2, 4].contains(&length) {
+                let endians: &[Endian] = if length == 1 {
+                    &[Endian::Little]
+                } else {
+                    &[Endian::Little, Endian::Big]
+                };
+                for endian in endians.iter() {
+                    let confidence = match endian {
+                        Endian::Little => 0.7,
+                        Endian::Big => 0.6,
+                    };
+                    let (before, after) = (to_integer(old, *endian), to_integer(new, *endian));
+                    let delta = sign_extend(after.wrapping_sub(before), length);
+                    if delta != 0 && delta.abs() <= ARITH_MAX {
+                        candidates.push((Mutation::Arithmetic(length, *endian, delta), confidence));
+                    }
+                    let value = sign_extend(after, length);
+                    if is_interesting(value, length) {
+                        candidates.push((
+                            Mutation::InterestingValue(length, *endian, value),
+                            confidence,
+                        ));
+                    }
+                }
+            }
+        }
+        _ => (),
+    }
+    if is_tail && !old.is_empty() && new.len() >= MIN_SPLICE_LENGTH && candidates.is_empty() {
+        candidates.push((Mutation::Splice, 0.5));
+    }
+    candidates.sort_by(|(_, a), (_, b)| b.partial_cmp(a).unwrap_or(std::cmp::Ordering::Equal));
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::mutation::{Endian, Mutation};
+    use super::MutationClassifier;
+    use crate::binary_diff::binary_diff_chunk::BinaryDiffChunk;
+    use crate::binary_diff::binary_diff_chunk::BinaryDiffChunk::{Copy, Delete, Replace, Same};
+    use crate::BinaryDiff;
+    use std::io::Cursor;
+
+    const ORIGINAL: [u8; 16] = [
+        0x10, 0x20, 0x30, 0x40, 0x50, 0x60, 0x70, 0x80, 0x90, 0xa0, 0xb0, 0xc0, 0xd0, 0xe0, 0xf0,
+        0x00,
+    ];
+
+    fn classify(chunks: Vec<BinaryDiffChunk>) -> Vec<(Mutation, f64)> {
+        let diff = BinaryDiff::from(&chunks);
+        MutationClassifier::from_slice(&diff, &ORIGINAL)
+            .classify()
+            .into_iter()
+            .map(|label| (label.mutation().clone(), label.confidence()))
+            .collect()
+    }
+
+    #[test]
+    fn test_classify_flips() {
+        assert_eq!(
+            classify(vec![Same(0, 1), Replace(1, 1, vec![0x21]), Same(2, 14)]),
+            vec![
+                (Mutation::BitFlip(1), 0.9),
+                (Mutation::Arithmetic(1, Endian::Little, 1), 0.7)
+            ]
+        );
+        assert_eq!(
+            classify(vec![
+                Same(0, 1),
+                Replace(1, 2, vec![0xdf, 0xcf]),
+                Same(3, 13)
+            ]),
+            vec![(Mutation::ByteFlip(2), 0.9)]
+        );
+    }
+
+    #[test]
+    fn test_classify_values() {
+        assert_eq!(
+            classify(vec![Replace(0, 2, vec![0xff, 0xff]), Same(2, 14)]),
+            vec![
+                (Mutation::InterestingValue(2, Endian::Little, -1), 0.7),
+                (Mutation::InterestingValue(2, Endian::Big, -1), 0.6)
+            ]
+        );
+        assert_eq!(
+            classify(vec![
+                Same(0, 2),
+                Replace(2, 2, vec![0x30, 0x3e]),
+                Same(4, 12)
+            ]),
+            vec![(Mutation::Arithmetic(2, Endian::Big, -2), 0.6)]
+        );
+    }
+
+    #[test]
+    fn test_classify_blocks() {
+        assert_eq!(
+            classify(vec![Same(0, 1), Delete(1, 2), Same(3, 13)]),
+            vec![(Mutation::BlockDeletion, 0.9)]
+        );
+        assert_eq!(
+            classify(vec![Same(0, 4), Copy(4, 0, 2), Same(4, 12)]),
+            vec![(Mutation::BlockDuplication(0), 0.9)]
+        );
+        assert_eq!(
+            classify(vec![Same(0, 8), Replace(8, 8, vec![1; 10])]),
+            vec![(Mutation::Splice, 0.5)]
+        );
+    }
+
+    #[test]
+    fn test_classify_delete_insert() {
+        let mut patched = ORIGINAL.to_vec();
+        patched[4] = 0x52;
+        let diff = BinaryDiff::from_slices(&ORIGINAL, &patched).unwrap();
+        let classifier = MutationClassifier::new(&diff, Cursor::new(ORIGINAL.to_vec())).unwrap();
+        let labels = classifier.classify();
+        assert_eq!(labels[0].mutation(), &Mutation::BitFlip(1));
+        assert_eq!(labels[0].original_range(), 4..5);
+        assert_eq!(labels[0].patched_range(), 4..5);
+        assert_eq!(
+            labels[1].mutation(),
+            &Mutation::Arithmetic(1, Endian::Little, 2)
+        );
+    }
+}
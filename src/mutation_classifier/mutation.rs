@@ -0,0 +1,82 @@
+use crate::BinaryDiffChunk;
+use std::fmt;
+use std::ops::Range;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Endian {
+    Little,
+    Big,
+}
+
+// Mutation operators of AFL
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Mutation {
+    // (flipped bits): Consecutive 1, 2 or 4 bits are flipped
+    BitFlip(usize),
+    // (flipped bytes): 1, 2 or 4 bytes are XORed with 0xff
+    ByteFlip(usize),
+    // (width, endian, delta): Value of 1, 2 or 4 bytes is added by small delta
+    Arithmetic(usize, Endian, i64),
+    // (width, endian, value): Value of 1, 2 or 4 bytes is overwritten by interesting value such as -1 or 0x7fff
+    InterestingValue(usize, Endian, i64),
+    BlockDeletion,
+    // (source): Block of original binary at `source` is inserted
+    BlockDuplication(usize),
+    // Tail of binary is replaced by tail of another binary
+    Splice,
+}
+
+impl fmt::Display for Mutation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BitFlip(bits) => write!(f, "BitFlip(bits={})", bits),
+            Self::ByteFlip(bytes) => write!(f, "ByteFlip(bytes={})", bytes),
+            Self::Arithmetic(width, endian, delta) => write!(
+                f,
+                "Arithmetic(width={}, endian={:?}, delta={})",
+                width, endian, delta
+            ),
+            Self::InterestingValue(width, endian, value) => write!(
+                f,
+                "InterestingValue(width={}, endian={:?}, value={})",
+                width, endian, value
+            ),
+            Self::BlockDeletion => write!(f, "BlockDeletion"),
+            Self::BlockDuplication(source) => write!(f, "BlockDuplication(source={:#x})", source),
+            Self::Splice => write!(f, "Splice"),
+        }
+    }
+}
+
+// Likely mutation that consecutive chunks other than Same() chunks result from
+#[derive(Debug, Clone, PartialEq)]
+pub struct MutationLabel<'a> {
+    pub(crate) chunks: &'a [BinaryDiffChunk],
+    pub(crate) original_range: Range<usize>,
+    pub(crate) patched_range: Range<usize>,
+    pub(crate) mutation: Mutation,
+    // In 0.0..=1.0
+    pub(crate) confidence: f64,
+}
+
+impl<'a> MutationLabel<'a> {
+    pub fn chunks(&self) -> &'a [BinaryDiffChunk] {
+        self.chunks
+    }
+
+    pub fn original_range(&self) -> Range<usize> {
+        self.original_range.clone()
+    }
+
+    pub fn patched_range(&self) -> Range<usize> {
+        self.patched_range.clone()
+    }
+
+    pub fn mutation(&self) -> &Mutation {
+        &self.mutation
+    }
+
+    pub fn confidence(&self) -> f64 {
+        self.confidence
+    }
+}
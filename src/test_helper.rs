@@ -0,0 +1,11 @@
+// Helpers shared by tests
+
+// Linear congruential generator to produce deterministic inputs
+pub(crate) fn random_bytes(seed: &mut u32, length: usize) -> Vec<u8> {
+    (0..length)
+        .map(|_| {
+            *seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+            (*seed >> 16) as u8
+        })
+        .collect()
+}
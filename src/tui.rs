@@ -124,31 +124,21 @@ fn render_xxd<'a>(
         let mut highlight_chunks: HashSet<usize> = HashSet::new();
         match compared_file {
             ComparedFile::Before(_) => {
-                for chunk in diff.chunks() {
-                    if let BinaryDiffChunk::Delete(offset, length) = chunk {
-                        for i in usize::from(offset.clone())..usize::from(offset + length) {
-                            highlight_chunks.insert(i.clone());
-                        }
+                for positioned in diff.iter_with_positions() {
+                    if let BinaryDiffChunk::Delete(_, _) = positioned.chunk() {
+                        highlight_chunks.extend(positioned.original_range());
                     }
                 }
             }
             ComparedFile::After(_) => {
-                let mut offset = 0;
-                for chunk in diff.chunks() {
-                    match chunk {
-                        BinaryDiffChunk::Insert(_, bytes) => {
-                            for i in offset..offset + bytes.len() {
-                                highlight_chunks.insert(i);
-                            }
-                            offset += bytes.len()
-                        }
-                        BinaryDiffChunk::Replace(_, _, bytes) => {
-                            for i in offset..offset + bytes.len() {
-                                highlight_chunks.insert(i);
-                            }
-                            offset += bytes.len()
+                for positioned in diff.iter_with_positions() {
+                    match positioned.chunk() {
+                        BinaryDiffChunk::Insert(_, _)
+                        | BinaryDiffChunk::Replace(_, _, _)
+                        | BinaryDiffChunk::Copy(_, _, _) => {
+                            highlight_chunks.extend(positioned.patched_range())
                         }
-                        BinaryDiffChunk::Same(_, length) => offset += length,
+                        BinaryDiffChunk::Same(_, _) => (),
                         BinaryDiffChunk::Delete(..) => (), // NOTE: This chunk does not affect after file
                     }
                 }